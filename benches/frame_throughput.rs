@@ -0,0 +1,71 @@
+//! Frame throughput benchmark
+//!
+//! Runs an optical model through the detector read, the PNG export of every frame
+//! and the long exposure sum, and reports the throughput of each stage in frames per second.
+//!
+//! ```shell
+//! cargo bench --features cpu --bench frame_throughput
+//! ```
+//! The CRSEO/CUDA model is benchmarked as well with the `ssr` feature.
+//! The number of frames is set with the `BENCH_N_FRAME` environment variable (default: 100).
+
+use std::{env, error::Error, fs, time::Instant};
+
+use gmt_cfd_psf::{MockOpticalModel, OpticalModel, PSFs, Stopwatch};
+
+fn throughput<M: OpticalModel>(
+    name: &str,
+    model: &mut M,
+    n_frame: usize,
+) -> Result<(), Box<dyn Error>> {
+    let dir = env::temp_dir().join("gmt-cfd-psf-bench").join(name);
+    fs::create_dir_all(&dir)?;
+
+    let mut stopwatch = Stopwatch::new();
+    let now = Instant::now();
+    let mut psfs = PSFs::new(&model.get_config());
+    for i in 0..n_frame {
+        let psf = stopwatch.time("detector read", || model.ray_trace().read_detector())?;
+        stopwatch.time("PNG export", || {
+            psf.save(dir.join(format!("frame_{i:06}.png")))
+        })?;
+        psfs.push(psf);
+    }
+    stopwatch.time("long exposure", || {
+        psfs.sum();
+    });
+    let elapsed = now.elapsed().as_secs_f64();
+
+    println!("{name}: {n_frame} frames in {elapsed:.3}s");
+    print!("{stopwatch}");
+    for stage in ["detector read", "PNG export"] {
+        if let Some(total) = stopwatch.total(stage) {
+            println!(
+                "{stage}: {:.1} frames/s",
+                n_frame as f64 / total.as_secs_f64()
+            );
+        }
+    }
+    println!("overall: {:.1} frames/s", n_frame as f64 / elapsed);
+    println!();
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let n_frame = env::var("BENCH_N_FRAME")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(100);
+
+    throughput(
+        "mock",
+        &mut MockOpticalModel::new(gmt_cfd_psf::DETECTOR_SIZE, 4.),
+        n_frame,
+    )?;
+
+    #[cfg(feature = "ssr")]
+    throughput("gmt", &mut gmt_cfd_psf::GmtOpticalModel::new()?, n_frame)?;
+
+    Ok(())
+}
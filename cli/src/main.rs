@@ -10,10 +10,19 @@ cargo r -r -- --help
 ```
 */
 
-use std::{env, fs::create_dir_all, path::Path, sync::Arc, time::Instant};
+use std::{
+    env,
+    fmt::Display,
+    fs::{File, create_dir_all},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
 use anyhow::anyhow;
-use clap::{Parser, ValueEnum};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum, parser::ValueSource};
+use futures::TryStreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
 use object_store::{ObjectStore, path::Path as ObjectPath};
 use parse_monitors::{
@@ -21,9 +30,13 @@ use parse_monitors::{
     cfd::{Baseline, BaselineTrait, CfdCase},
 };
 use psf::{
-    AzimuthAngle, GmtOpticalModel, PSFs, StorePath, WindSpeed, ZenithAngle, get_enclosure_config,
+    AzimuthAngle, Convergence, GmtOpticalModel, GmtOpticalModelError, LongExposure, N_ZERNIKE,
+    OpdStats, PSFs, RbmSeries, RunConfig, Stopwatch, StorePath, WindLoads, WindSpeed, ZenithAngle,
 };
 
+/// Minimum number of frames integrated before the PSSn convergence is tested
+const MIN_CONVERGED_FRAMES: usize = 10;
+
 #[derive(Debug, Clone, ValueEnum)]
 enum Exposure {
     Short,
@@ -40,7 +53,7 @@ struct Args {
 
     /// Enable wind loads effects
     #[arg(long, value_enum)]
-    windloads: Option<Option<WindLoadsOptions>>,
+    windloads: Option<Option<RbmSeries>>,
 
     /// Zenith angle in degrees (0, 30, or 60)
     #[arg(long, value_enum, default_value_t = ZenithAngle::Thirty)]
@@ -54,26 +67,321 @@ struct Args {
     #[arg(long, value_enum, default_value_t = WindSpeed::Seven)]
     wind_speed: WindSpeed,
 
+    /// CFD case name (e.g. zen30az000_CD12) instead of the zenith angle,
+    /// the azimuth angle and the wind speed, the enclosure configuration is not inferred
+    #[arg(long, conflicts_with_all = ["zenith_angle", "azimuth_angle", "wind_speed", "from_config"])]
+    cfd_case: Option<String>,
+
     /// Writes the OPD map of the corresponding PSF to a png image
     #[arg(long)]
     opd: bool,
 
+    /// Encodes the short exposure PSFs, and the OPD maps with `--opd`, into animated GIFs at 5Hz
+    #[arg(long, conflicts_with = "long_only")]
+    animation: bool,
+
     /// Sets the number of frames
     #[arg(short, long, default_value_t = 100)]
     n_frame: usize,
 
+    /// Write the per-frame OPD RMS, peak-to-valley and first Zernike coefficients to a CSV file
+    #[arg(long)]
+    opd_csv: Option<PathBuf>,
+
+    /// Write the per-frame PSSn, Strehl ratio, peak and centroid to a CSV file
+    #[arg(long, conflicts_with = "long_only")]
+    metrics_csv: Option<PathBuf>,
+
     /// Do not save short exposure PSFs as images
     #[arg(long)]
     no_shorts: bool,
+
+    /// Do not save the diffraction limited reference PSF as psf.png
+    #[arg(long)]
+    no_reference: bool,
+
+    /// Print the PSSn and the Strehl ratio of each short exposure PSF
+    #[arg(long, conflicts_with_all = ["long_only", "compare"])]
+    strehl: bool,
+
+    /// Only save the short exposure PSFs with the given frame indices (e.g. 0,49,99)
+    #[arg(long, value_delimiter = ',', conflicts_with = "no_shorts")]
+    frames: Option<Vec<usize>>,
+
+    /// Only integrate the long exposure PSF without storing the short exposure frames
+    #[arg(long, conflicts_with_all = ["opd", "opd_csv", "no_shorts", "frames"])]
+    long_only: bool,
+
+    /// Stop integrating the long exposure once the cumulative PSSn changes by less than
+    /// this tolerance from one frame to the next, `n_frame` being the maximum number of frames
+    #[arg(long, requires = "long_only")]
+    converge: Option<f64>,
+
+    /// Compare the long exposure PSFs and PSSn of two wind loads RBM series (e.g. fsm,asm2)
+    /// of the same CFD case
+    #[arg(long, value_enum, value_delimiter = ',', num_args = 2, conflicts_with_all = ["windloads", "rbm_path", "long_only", "opd", "opd_csv", "frames", "no_shorts"])]
+    compare: Option<Vec<RbmSeries>>,
+
+    /// Print the resolved CFD case, data paths and outputs, then exit
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Bin the PSF frames by summing blocks of bin×bin detector pixels
+    #[arg(long, default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    bin: usize,
+
+    /// Scale the wind loads rigid body motions by this gain
+    #[arg(long, default_value_t = 1.)]
+    windload_gain: f64,
+
+    /// Remove the atmospheric seeing contribution (telescope-only PSF)
+    #[arg(long)]
+    no_atmosphere: bool,
+
+    /// Print the wall-clock time spent in each stage of the pipeline
+    #[arg(long)]
+    timing: bool,
+
+    /// Path in the object store to the M1 & M2 RBMs parquet file,
+    /// overriding the path derived from the CFD case
+    #[arg(long, requires = "windloads")]
+    rbm_path: Option<String>,
+
+    /// Directory the images and the run metadata are written to
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+
+    /// Prefix of the output images and frames directory names (e.g. zen30az000_)
+    #[arg(long, default_value = "")]
+    prefix: String,
+
+    /// Read the run configuration (`run_config.json`) saved by the web application,
+    /// the flags given explicitly override the file
+    #[arg(long)]
+    from_config: Option<PathBuf>,
 }
-#[derive(Debug, Clone, ValueEnum)]
-enum WindLoadsOptions {
-    /// Compensate the segment tip-tilt with the FSM
-    Fsm,
-    /// Compensate the segment piston and tip-tilt with the ASM
-    Asm,
-    /// Compensate the segment piston (updated RTF) and tip-tilt with the ASM
-    Asm2,
+
+impl From<&Args> for RunConfig {
+    fn from(args: &Args) -> Self {
+        Self {
+            domeseeing: args.domeseeing,
+            windloads: args
+                .windloads
+                .map(|series| series.unwrap_or(RbmSeries::OpenLoop))
+                .or_else(|| {
+                    args.compare
+                        .as_ref()
+                        .and_then(|series| series.first().copied())
+                }),
+            zenith_angle: args.zenith_angle,
+            azimuth_angle: args.azimuth_angle,
+            wind_speed: args.wind_speed,
+            enclosure: None,
+        }
+    }
+}
+
+impl Args {
+    /// Returns the path of an output file in the output directory, prefixed with the prefix
+    fn output(&self, name: impl Display) -> PathBuf {
+        self.output_dir.join(format!("{}{}", self.prefix, name))
+    }
+    /// Parses the command line arguments, filling in the arguments not given explicitly
+    /// from the configuration file if any
+    fn parse_with_config() -> anyhow::Result<Self> {
+        let matches = Self::command().get_matches();
+        let mut args = Self::from_arg_matches(&matches)?;
+        if let Some(path) = args.from_config.as_ref() {
+            let config: RunConfig = serde_json::from_reader(File::open(path)?)?;
+            args.merge(config, &matches);
+        }
+        Ok(args)
+    }
+    fn merge(&mut self, config: RunConfig, matches: &ArgMatches) {
+        let is_default = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+        if is_default("domeseeing") {
+            self.domeseeing = config.domeseeing;
+        }
+        if is_default("windloads") && config.windloads.is_some() {
+            self.windloads = Some(config.windloads);
+        }
+        if is_default("zenith_angle") {
+            self.zenith_angle = config.zenith_angle;
+        }
+        if is_default("azimuth_angle") {
+            self.azimuth_angle = config.azimuth_angle;
+        }
+        if is_default("wind_speed") {
+            self.wind_speed = config.wind_speed;
+        }
+    }
+}
+
+/// Per-frame OPD statistics CSV file, written one line per frame so partial runs are usable
+struct OpdCsv(File);
+impl OpdCsv {
+    fn create(path: &Path) -> anyhow::Result<Self> {
+        let mut file = File::create(path)?;
+        write!(file, "frame,opd_rms,opd_pv")?;
+        for j in 1..=N_ZERNIKE {
+            write!(file, ",z{j}")?;
+        }
+        writeln!(file)?;
+        Ok(Self(file))
+    }
+    fn write(&mut self, frame: usize, opd: &[f32]) -> anyhow::Result<()> {
+        let Some(stats) = OpdStats::new(opd, N_ZERNIKE) else {
+            return Ok(());
+        };
+        let mut line = format!("{frame},{:e},{:e}", stats.rms, stats.pv);
+        for c in &stats.zernike {
+            line.push_str(&format!(",{c:e}"));
+        }
+        writeln!(self.0, "{line}")?;
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+/// Object store path to the CFD dome seeing OPD data of a CFD case
+fn domeseeing_path(cfd_case: &CfdCase<CFD_YEAR>) -> anyhow::Result<ObjectPath> {
+    let baseline = Baseline::<CFD_YEAR>::path()?;
+    let baseline = baseline
+        .to_str()
+        .ok_or_else(|| anyhow!("invalid CFD baseline path {:?}", baseline))?;
+    Ok(ObjectPath::from(baseline).join(cfd_case.to_string()))
+}
+
+/// Object store path to the M1 & M2 rigid body motions parquet file of a CFD case
+fn windloads_path(cfd_case: &CfdCase<CFD_YEAR>, series: RbmSeries) -> anyhow::Result<ObjectPath> {
+    Ok(ObjectPath::new(env::var("FEM")?)
+        .join("cfd")
+        .join(cfd_case.to_string())
+        .join(series.file_name()))
+}
+
+/// Returns the number of dome seeing OPD maps of a CFD case
+async fn domeseeing_len(store: &dyn ObjectStore, cfd_path: &ObjectPath) -> anyhow::Result<usize> {
+    let mut n = 0;
+    let mut objects = store.list(Some(cfd_path));
+    while let Some(meta) = objects.try_next().await? {
+        if meta
+            .location
+            .filename()
+            .is_some_and(|name| name.starts_with("optvol_optvol_"))
+        {
+            n += 1;
+        }
+    }
+    Ok(n)
+}
+
+/// Returns the number of frames to process, clamped to the wind loads and dome seeing
+/// samples available, if any
+fn clamp_n_frame(
+    mut n_frame: usize,
+    windloads_len: Option<usize>,
+    domeseeing_len: Option<usize>,
+) -> usize {
+    for (n, data) in [
+        (windloads_len, "wind loads"),
+        (domeseeing_len, "dome seeing"),
+    ] {
+        match n {
+            Some(n) if n < n_frame => {
+                println!(
+                    "⚠️  Only {} {} samples available, processing {} frames instead of {}",
+                    n, data, n, n_frame
+                );
+                n_frame = n;
+            }
+            _ => (),
+        }
+    }
+    n_frame
+}
+
+/// Integrates the long exposure PSFs of the same CFD case for two RBM series
+/// and saves both long exposures and their difference
+async fn compare(
+    args: &Args,
+    store: Arc<dyn ObjectStore>,
+    run_config: &RunConfig,
+    cfd_case: &CfdCase<CFD_YEAR>,
+    cfd_path: Option<ObjectPath>,
+    series: [RbmSeries; 2],
+) -> anyhow::Result<()> {
+    let mut long_exposures = vec![];
+    let mut metadata = vec![];
+    for series in series {
+        let name = series
+            .to_possible_value()
+            .map_or_else(|| series.to_string(), |value| value.get_name().to_string());
+        println!("Integrating the long exposure PSF with the {name} RBMs");
+        let mut gmt = if args.no_atmosphere {
+            GmtOpticalModel::without_atmosphere()?
+        } else {
+            GmtOpticalModel::new()?
+        };
+        let run_config = RunConfig {
+            windloads: Some(series),
+            ..run_config.clone()
+        };
+        if let Some(turbulence_effects) = run_config.turbulence_effects() {
+            gmt.set_config(gmt.get_config().turbulence_effects(turbulence_effects));
+        }
+        gmt.set_config(gmt.get_config().cfd_case(cfd_case));
+        let gmt = if let Some(cfd_path) = cfd_path.clone() {
+            gmt.domeseeing_from_store(store.clone(), cfd_path).await?
+        } else {
+            gmt
+        };
+        let mut gmt = gmt
+            .windloads(store.clone(), windloads_path(cfd_case, series)?)
+            .await?;
+        gmt.set_windloads_gain(args.windload_gain);
+
+        let mut long_exposure = LongExposure::new(&gmt.get_config().binned(args.bin));
+        let pb = ProgressBar::new(args.n_frame as u64);
+        for psf in gmt.frames(args.n_frame) {
+            long_exposure.add(psf?.bin(args.bin));
+            pb.inc(1);
+        }
+        pb.finish();
+        metadata.push(serde_json::to_value(long_exposure.metadata())?);
+        let psf = long_exposure.psf();
+        let filename = args.output(format_args!("long_exposure_psf_{name}.png"));
+        psf.save(&filename)?;
+        println!("🖼️  Long exposure PSF saved as {}", filename.display());
+        long_exposures.push((name, psf));
+    }
+    // Run metadata of both long exposures
+    serde_json::to_writer_pretty(
+        File::create(args.output_dir.join("run_metadata.json"))?,
+        &metadata,
+    )?;
+
+    let [(name_a, psf_a), (name_b, psf_b)]: [_; 2] = long_exposures
+        .try_into()
+        .map_err(|_| anyhow!("expected 2 long exposure PSFs"))?;
+    let difference = psf_b
+        .difference(&psf_a)
+        .ok_or_else(|| anyhow!("cannot compute the difference of the long exposure PSFs"))?;
+    let filename = args.output("long_exposure_psf_difference.png");
+    difference.save(&filename)?;
+    println!(
+        "🖼️  Difference ({name_b} - {name_a}) saved as {}",
+        filename.display()
+    );
+    if let (Some(pssn_a), Some(pssn_b)) = (psf_a.pssn(), psf_b.pssn()) {
+        println!("📈 PSSn ({name_a}): {:.5}", pssn_a);
+        println!("📈 PSSn ({name_b}): {:.5}", pssn_b);
+        println!(
+            "📈 PSSn delta ({name_b} - {name_a}): {:+.5}",
+            pssn_b - pssn_a
+        );
+    }
+    Ok(())
 }
 
 #[tokio::main]
@@ -81,7 +389,7 @@ async fn main() -> anyhow::Result<()> {
     env_logger::init();
 
     // Parse command line arguments
-    let args = Args::parse();
+    let args = Args::parse_with_config()?;
 
     #[cfg(feature = "s3")]
     dotenvy::from_filename(".env_s3")?;
@@ -100,37 +408,33 @@ async fn main() -> anyhow::Result<()> {
         object_store::local::LocalFileSystem::new_with_prefix("/home/rconan/maua")?,
     );
 
-    // Setup GMT optics and imaging
-    let mut gmt = GmtOpticalModel::new()?;
-
-    // Generate reference frame (no turbulence)
-    gmt.ray_trace().read_detector().save("psf.png")?;
-    println!("Saved frame0 as psf.png");
-
-    // Generate turbulence effects string
-    let windloads_tag = args.windloads.as_ref().map(|windloads| match windloads {
-        Some(options) => match options {
-            WindLoadsOptions::Fsm => "(Wind Loads - FSM)",
-            WindLoadsOptions::Asm => "(Wind Loads - ASM)",
-            WindLoadsOptions::Asm2 => "(Wind Loads - ASM2)",
-        },
-        None => "WindLoads",
-    });
-    let turbulence_effects = match (args.domeseeing, windloads_tag) {
-        (true, None) => Some("Dome Seeing".to_string()),
-        (true, Some(tag)) => Some(format!("Dome Seeing + {tag}")),
-        (false, Some(tag)) => Some(tag.to_string()),
-        (false, None) => return Err(anyhow!("you must select either domeseeing or windloads")),
+    // CFD case either from its name, with the enclosure configuration given by the name,
+    // or from the zenith angle, the azimuth angle and the wind speed
+    let run_config = match args.cfd_case.as_deref() {
+        Some(name) => {
+            let RunConfig {
+                domeseeing,
+                windloads,
+                ..
+            } = RunConfig::from(&args);
+            RunConfig {
+                domeseeing,
+                windloads,
+                ..name.parse()?
+            }
+        }
+        None => RunConfig::from(&args),
     };
 
-    turbulence_effects
-        .map(|value| gmt.set_config(gmt.get_config().turbulence_effects(value)));
+    // Generate turbulence effects string
+    let turbulence_effects = run_config
+        .turbulence_effects()
+        .ok_or_else(|| anyhow!("you must select either domeseeing or windloads"))?;
 
-    // CFD case - extract values from arguments
-    let zenith = u32::from(args.zenith_angle);
-    let azimuth = u32::from(args.azimuth_angle);
-    let wind_speed = u32::from(args.wind_speed);
-    let enclosure = get_enclosure_config(wind_speed, args.zenith_angle);
+    let zenith = run_config.zenith_angle.as_u32();
+    let azimuth = run_config.azimuth_angle.as_u32();
+    let wind_speed = run_config.wind_speed.as_u32();
+    let enclosure = run_config.enclosure();
 
     println!("CFD Configuration:");
     println!("  Zenith angle: {}°", zenith);
@@ -138,46 +442,151 @@ async fn main() -> anyhow::Result<()> {
     println!("  Wind speed: {} m/s", wind_speed);
     println!("  Enclosure: {}", enclosure);
 
-    let cfd_case = CfdCase::<CFD_YEAR>::colloquial(zenith, azimuth, enclosure, wind_speed)?;
+    let cfd_case = CfdCase::<CFD_YEAR>::colloquial(zenith, azimuth, enclosure, wind_speed)
+        .map_err(|_| GmtOpticalModelError::invalid_cfd_case(&run_config, CFD_YEAR))?;
+
+    // Data paths
+    let cfd_path = run_config
+        .domeseeing
+        .then(|| domeseeing_path(&cfd_case))
+        .transpose()?;
+    let rbms_path = match args.rbm_path.as_ref() {
+        Some(path) => Some(ObjectPath::from(path.as_str())),
+        None => run_config
+            .windloads
+            .map(|series| windloads_path(&cfd_case, series))
+            .transpose()?,
+    };
+
+    if args.dry_run {
+        println!("Plan:");
+        println!("  CFD case: {}", cfd_case);
+        println!("  Turbulence effects: {}", turbulence_effects);
+        if let Some(path) = cfd_path.as_ref() {
+            println!("  Dome seeing OPD path: {}", path);
+        }
+        if let Some(path) = rbms_path.as_ref() {
+            println!("  Wind loads RBMs object: {}", path);
+        }
+        let windloads_len = match rbms_path.as_ref() {
+            Some(path) if args.rbm_path.is_some() => Some(
+                WindLoads::new_checked(store.clone(), path.clone())
+                    .await?
+                    .len(),
+            ),
+            Some(path) => Some(WindLoads::new(store.clone(), path.clone()).await?.len()),
+            None => None,
+        };
+        let domeseeing_len = match cfd_path.as_ref() {
+            Some(path) => Some(domeseeing_len(&*store, path).await?),
+            None => None,
+        };
+        println!(
+            "  Number of frames: {}",
+            clamp_n_frame(args.n_frame, windloads_len, domeseeing_len)
+        );
+        println!("  Outputs:");
+        if let Some(series) = args.compare.as_ref() {
+            for series in series {
+                if let Some(value) = series.to_possible_value() {
+                    println!(
+                        "    {}",
+                        args.output(format_args!("long_exposure_psf_{}.png", value.get_name()))
+                            .display()
+                    );
+                }
+            }
+            println!(
+                "    {}",
+                args.output("long_exposure_psf_difference.png").display()
+            );
+            println!(
+                "    {}",
+                args.output_dir.join("run_metadata.json").display()
+            );
+            return Ok(());
+        }
+        if !args.no_reference {
+            println!("    {}", args.output("psf.png").display());
+        }
+        println!("    {}", args.output("long_exposure_psf.png").display());
+        if !args.long_only {
+            if !args.no_shorts {
+                println!("    {}/", args.output("frames").display());
+            }
+            println!(
+                "    {}, {}",
+                args.output("best.png").display(),
+                args.output("worst.png").display()
+            );
+        }
+        println!(
+            "    {}",
+            args.output_dir.join("run_metadata.json").display()
+        );
+        return Ok(());
+    }
+
+    create_dir_all(&args.output_dir)?;
+
+    if let Some(series) = args.compare.as_deref() {
+        return compare(
+            &args,
+            store,
+            &run_config,
+            &cfd_case,
+            cfd_path,
+            [series[0], series[1]],
+        )
+        .await;
+    }
+
+    // Setup GMT optics and imaging
+    let mut gmt = if args.no_atmosphere {
+        GmtOpticalModel::without_atmosphere()?
+    } else {
+        GmtOpticalModel::new()?
+    };
+
+    // Generate reference frame (no turbulence)
+    if !args.no_reference {
+        let filename = args.output("psf.png");
+        gmt.reference_psf()?.save(&filename)?;
+        println!("Saved the reference frame as {}", filename.display());
+    }
+
+    gmt.set_config(gmt.get_config().turbulence_effects(turbulence_effects));
     gmt.set_config(gmt.get_config().cfd_case(cfd_case));
 
-    let gmt = if args.domeseeing {
-        let cfd_path = ObjectPath::from(Baseline::<CFD_YEAR>::path()?.to_str().unwrap())
-            .join(cfd_case.to_string());
-        gmt.domeseeing(store.clone(), cfd_path).await?
+    let (gmt, domeseeing_len) = if let Some(cfd_path) = cfd_path {
+        let n = domeseeing_len(&*store, &cfd_path).await?;
+        (
+            gmt.domeseeing_from_store(store.clone(), cfd_path).await?,
+            Some(n),
+        )
     } else {
-        gmt
+        (gmt, None)
     };
 
-    let mut gmt = match args.windloads.as_ref() {
-        None => gmt,
-        Some(m2) => {
-            let object = match m2 {
-                Some(m2) => match m2 {
-                    WindLoadsOptions::Fsm => "m1_m2_rbms.FSM.parquet",
-                    WindLoadsOptions::Asm => "m1_m2_rbms.ASM.parquet",
-                    WindLoadsOptions::Asm2 => "m1_m2_rbms.ASM.2.parquet",
-                },
-                None => "m1_m2_rbms.parquet",
-            };
-            let rbms_path = ObjectPath::new(env::var("FEM")?)
-                .join("cfd")
-                .join(cfd_case.to_string())
-                .join(object);
+    let mut gmt = if let Some(rbms_path) = rbms_path {
+        if args.rbm_path.is_some() {
+            gmt.windloads_from(store.clone(), rbms_path).await?
+        } else {
             gmt.windloads(store.clone(), rbms_path).await?
         }
+    } else {
+        gmt
     };
+    gmt.set_windloads_gain(args.windload_gain);
 
-    // Setup output directory
-    let frames_dir = Path::new("frames");
-    create_dir_all(frames_dir)?;
+    // The wind loads and dome seeing time series may hold fewer samples than requested
+    let n_frame = clamp_n_frame(args.n_frame, gmt.windloads_len(), domeseeing_len);
 
     // Process turbulence-affected frames
     let now = Instant::now();
-    let mut psfs = PSFs::from(&gmt);
 
     // Create progress bar for frame processing
-    let process_pb = ProgressBar::new(args.n_frame as u64);
+    let process_pb = ProgressBar::new(n_frame as u64);
     process_pb.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
@@ -186,57 +595,204 @@ async fn main() -> anyhow::Result<()> {
     );
     process_pb.set_message("Processing PSF frames");
 
-    if args.opd {
-        for _ in 0..args.n_frame {
-            psfs.push(
-                gmt.ray_trace()
-                    .read_detector()
-                    .opd(gmt.get_opd())
-                    .pssn_value(gmt.compute_pssn()),
-            );
-            process_pb.inc(1);
+    let mut stopwatch = Stopwatch::new();
+
+    if args.long_only {
+        let mut long_exposure = LongExposure::new(&gmt.get_config().binned(args.bin));
+        let frame_count = if let Some(tol) = args.converge {
+            process_pb.set_message("Integrating PSF frames until the PSSn converges");
+            let (n_frame, pssn, convergence) = stopwatch.time("ray tracing", || {
+                gmt.run_until_converged(tol, MIN_CONVERGED_FRAMES, n_frame)
+            });
+            match convergence {
+                Convergence::Converged => (),
+                Convergence::MaxFrames => process_pb.println(format!(
+                    "⚠️  The PSSn has not converged within {} frames",
+                    n_frame
+                )),
+                Convergence::OutOfData => process_pb.println(format!(
+                    "⚠️  The PSSn has not converged before the end of the data, after {} frames",
+                    n_frame
+                )),
+            }
+            let psf = stopwatch.time("detector read", || gmt.read_detector_binned(args.bin))?;
+            long_exposure.add(psf.pssn_value(pssn));
+            process_pb.set_position(n_frame as u64);
+            n_frame
+        } else {
+            let mut frames = gmt.frames(n_frame);
+            while let Some(psf) = stopwatch.time("frames", || frames.next()) {
+                long_exposure.add(psf?.without_opd().bin(args.bin));
+                process_pb.inc(1);
+            }
+            long_exposure.len()
+        };
+        process_pb.finish_with_message("PSF processing complete");
+        long_exposure.save_metadata(&args.output_dir)?;
+        let psf = long_exposure.psf();
+        let long_exposure_path = args.output("long_exposure_psf.png");
+        stopwatch.time("PNG encoding", || psf.save(&long_exposure_path))?;
+
+        println!();
+        println!(
+            "✅ Processing completed in {:.2}s",
+            now.elapsed().as_secs_f64()
+        );
+        println!("🔢 Integrated {} frames", frame_count);
+        if let Some(pssn) = psf.pssn() {
+            println!("📈 PSSn: {:.5}", pssn);
         }
-    } else {
-        for _ in 0..args.n_frame {
-            psfs.push(
-                gmt.ray_trace()
-                    .read_detector()
-                    .pssn_value(gmt.compute_pssn()),
+        if !args.no_reference {
+            println!(
+                "🖼️  Reference PSF saved as {}",
+                args.output("psf.png").display()
             );
-            process_pb.inc(1);
         }
+        println!(
+            "🖼️  Long exposure PSF saved as {}",
+            long_exposure_path.display()
+        );
+        println!(
+            "📝 Run metadata saved as {}",
+            args.output_dir.join("run_metadata.json").display()
+        );
+        if args.timing {
+            println!();
+            print!("{stopwatch}");
+        }
+        return Ok(());
+    }
+
+    // Setup output directory
+    let frames_dir = args.output("frames");
+    create_dir_all(&frames_dir)?;
+
+    let mut psfs = PSFs::new(&gmt.get_config().binned(args.bin));
+    let mut opd_csv = args.opd_csv.as_deref().map(OpdCsv::create).transpose()?;
+
+    let mut frames = gmt.frames(n_frame);
+    while let Some(psf) = stopwatch.time("frames", || frames.next()) {
+        let psf = psf?;
+        let psf = if args.strehl {
+            let strehl = stopwatch.time("Strehl", || frames.model().compute_strehl(&psf))?;
+            psf.strehl_value(strehl)
+        } else {
+            psf
+        }
+        .bin(args.bin);
+        if let Some(opd_csv) = opd_csv.as_mut() {
+            opd_csv.write(psfs.len(), frames.model().get_opd())?;
+        }
+        let psf = if args.opd { psf } else { psf.without_opd() };
+        if let (Some(pssn), Some(strehl)) = (psf.pssn(), psf.strehl()) {
+            process_pb.println(format!(
+                "frame {:03}: PSSn {:.5}, Strehl {:.3}",
+                psfs.len(),
+                pssn,
+                strehl
+            ));
+        }
+        psfs.push(psf);
+        process_pb.inc(1);
     }
 
-    let frame_count = psfs.len();
+    let frame_count = args
+        .frames
+        .as_ref()
+        .map_or(psfs.len(), |indices| indices.len());
 
     // Save all turbulence frames with consistent normalization
-    if !args.no_shorts {
+    if let Some(indices) = args.frames.as_ref() {
+        process_pb.finish_with_message("PSF processing complete");
+        stopwatch.time("PNG encoding", || psfs.save_frames(&frames_dir, indices))?;
+    } else if !args.no_shorts {
         process_pb.reset();
         process_pb.set_message("Saving frames");
-        psfs.save_all_frames("frames", &process_pb)?;
+        stopwatch.time("PNG encoding", || {
+            psfs.save_all_frames(&frames_dir, &process_pb, None)
+        })?;
         process_pb.finish_with_message("All frames saved");
     } else {
         process_pb.finish_with_message("PSF processing complete");
     }
 
-    psfs.sum().save("long_exposure_psf.png")?;
+    let long_exposure_path = args.output("long_exposure_psf.png");
+    stopwatch.time("PNG encoding", || psfs.sum().save(&long_exposure_path))?;
+    if let Some((i, psf)) = psfs.best_pssn() {
+        let filename = args.output("best.png");
+        psf.save(&filename)?;
+        println!(
+            "Saved frame {i} with the best PSSn as {}",
+            filename.display()
+        );
+    }
+    if let Some((i, psf)) = psfs.worst_pssn() {
+        let filename = args.output("worst.png");
+        psf.save(&filename)?;
+        println!(
+            "Saved frame {i} with the worst PSSn as {}",
+            filename.display()
+        );
+    }
+    if args.animation {
+        stopwatch.time("GIF encoding", || {
+            psfs.save_animation(args.output("psf_animation.gif"), 5.)
+        })?;
+        if args.opd {
+            stopwatch.time("GIF encoding", || {
+                psfs.save_opd_animation(args.output("opd_animation.gif"), 5.)
+            })?;
+        }
+    }
+    if let Some(path) = args.metrics_csv.as_ref() {
+        psfs.write_metrics_csv(path)?;
+        println!("Saved the per-frame metrics as {}", path.display());
+    }
+    psfs.save_metadata(&args.output_dir)?;
 
     println!();
     println!(
         "✅ Processing completed in {:.2}s",
         now.elapsed().as_secs_f64()
     );
-    println!("📁 Saved {} frames to ./frames/ directory", frame_count);
-    println!("🖼️  Reference PSF saved as psf.png");
-    println!("🖼️  Long exposure PSF saved as long_exposure_psf.png");
+    println!(
+        "📁 Saved {} frames to {}/ directory",
+        frame_count,
+        frames_dir.display()
+    );
+    if !args.no_reference {
+        println!(
+            "🖼️  Reference PSF saved as {}",
+            args.output("psf.png").display()
+        );
+    }
+    println!(
+        "🖼️  Long exposure PSF saved as {}",
+        long_exposure_path.display()
+    );
+    println!(
+        "📝 Run metadata saved as {}",
+        args.output_dir.join("run_metadata.json").display()
+    );
+    if args.animation {
+        println!(
+            "🎬 PSF animation saved as {}",
+            args.output("psf_animation.gif").display()
+        );
+        if args.opd {
+            println!(
+                "🎬 OPD animation saved as {}",
+                args.output("opd_animation.gif").display()
+            );
+        }
+    }
     println!();
-    if args.opd {
-        println!("🎬 To create animated GIFs at 5Hz, run:");
-        println!("   convert -delay 20 -loop 0 frames/frame_*.png psf_animation.gif ; \\");
-        println!("   convert -delay 20 -loop 0 frames/opd_*.png opd_animation.gif");
-    } else {
-        println!("🎬 To create an animated GIF at 5Hz, run:");
-        println!("   convert -delay 20 -loop 0 frames/frame_*.png psf_animation.gif");
-    };
+    if !args.animation {
+        println!("🎬 To create animated GIFs at 5Hz, run again with --animation");
+    }
+    if args.timing {
+        println!();
+        print!("{stopwatch}");
+    }
     Ok(())
 }
@@ -8,21 +8,99 @@ export CFD_REPO=~/maua/CASES/
 export GMT_MODES_PATH=~/Dropbox/AWS/CEO/gmtMirrors/
 cargo r -r -- --help
 ```
+
+The PSSn of each CFD case is written to `cfd_domeseeing-windloads_h-pssn/<case>.json`
+as soon as the case completes and the cases with an existing output are skipped,
+so an interrupted batch resumes where it stopped.
+The PSSn of all the cases are finally gathered into `cfd_domeseeing-windloads_h-pssn.pkl`.
 */
 
-use std::{collections::BTreeMap, env, fs::File, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, File},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
 
+use clap::Parser;
 use indicatif::{MultiProgress, ProgressBar};
 use object_store::{ObjectStore, path::Path as ObjectPath};
 use parse_monitors::{
     CFD_YEAR,
     cfd::{Baseline, BaselineTrait},
 };
-use psf::{GmtOpticalModel, StorePath};
+use psf::{CfdCaseKey, GmtOpticalModel, RbmSeries, RetryPolicy, StorePath};
+use serde::{Deserialize, Serialize, Serializer};
+use tokio::sync::Semaphore;
+
+#[derive(Parser)]
+#[command(name = "pssn-batch")]
+#[command(
+    about = "Compute the H band PSSn of the CFD baseline cases with dome seeing and wind loads"
+)]
+struct Args {
+    /// Maximum number of CFD cases processed concurrently [default: number of CPUs]
+    #[arg(short, long)]
+    jobs: Option<NonZeroUsize>,
+}
+
+/// Directory of the per-case PSSn outputs
+const CASES_DIR: &str = "cfd_domeseeing-windloads_h-pssn";
+
+/// PSSn of a CFD case
+#[derive(Serialize, Deserialize)]
+struct CasePssn {
+    cfd_case: String,
+    pssn: f64,
+}
+impl CasePssn {
+    /// Path to the output of a CFD case
+    fn path(case: &str) -> PathBuf {
+        Path::new(CASES_DIR).join(case).with_extension("json")
+    }
+    /// Writes the output of a CFD case
+    ///
+    /// The output is written to a temporary file first and then renamed,
+    /// so an interrupted run never leaves a truncated output behind
+    fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path(&self.cfd_case);
+        let tmp = path.with_extension("json.tmp");
+        serde_json::to_writer_pretty(File::create(&tmp)?, self)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+    fn load(case: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_reader(File::open(Self::path(case))?)?)
+    }
+}
+
+/// PSSn of the CFD cases keyed by CFD case names, in sweep order
+#[derive(Default)]
+struct SweepPssn(BTreeMap<CfdCaseKey, (String, f64)>);
+impl SweepPssn {
+    fn insert(&mut self, case: String, pssn: f64) -> anyhow::Result<()> {
+        let key: CfdCaseKey = case.parse()?;
+        self.0.insert(key, (case, pssn));
+        Ok(())
+    }
+}
+impl Serialize for SweepPssn {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.values().map(|(case, pssn)| (case, pssn)))
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     env_logger::init();
+    let args = Args::parse();
+    let jobs = args
+        .jobs
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get);
 
     dotenvy::from_filename(".env_s3")?;
 
@@ -33,51 +111,69 @@ async fn main() -> anyhow::Result<()> {
             .build()?,
     );
 
-    let mut pssns = BTreeMap::<String, f64>::new();
-    for cfd_case_chunk in Baseline::<CFD_YEAR>::default()
-        .into_iter()
-        .collect::<Vec<_>>()
-        .chunks(8)
-    {
-        let mpb = MultiProgress::new();
-        let mut h = vec![];
-        for cfd_case in cfd_case_chunk.into_iter().cloned() {
-            let clone_store = store.clone();
-            // let cfd_case = cfd_case_.clone();
+    fs::create_dir_all(CASES_DIR)?;
+    let cfd_cases: Vec<_> = Baseline::<CFD_YEAR>::default().into_iter().collect();
+    let pending: Vec<_> = cfd_cases
+        .iter()
+        .filter(|cfd_case| !CasePssn::path(&cfd_case.to_string()).exists())
+        .cloned()
+        .collect();
+    if pending.len() < cfd_cases.len() {
+        println!(
+            "Skipping {} completed CFD cases",
+            cfd_cases.len() - pending.len()
+        );
+    }
+    // a new case starts as soon as one of the `jobs` slots is released
+    let semaphore = Arc::new(Semaphore::new(jobs));
+    let mpb = MultiProgress::new();
+    let mut h = vec![];
+    for cfd_case in pending {
+        let clone_store = store.clone();
+        let semaphore = semaphore.clone();
+        let mpb = mpb.clone();
+        h.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
             let pb = mpb.add(ProgressBar::new_spinner().with_message(cfd_case.to_string()));
-            h.push(tokio::spawn(async move {
-                // println!("{}", cfd_case);
-                // Setup GMT optics and imaging
-                let gmt = GmtOpticalModel::builder().h_band().build()?;
-
-                let gmt = {
-                    let cfd_path =
-                        ObjectPath::from(Baseline::<CFD_YEAR>::path()?.to_str().unwrap())
-                            .join(cfd_case.to_string());
-                    gmt.domeseeing(clone_store.clone(), cfd_path).await?
-                };
-
-                let mut gmt = {
-                    let object = "m1_m2_rbms.parquet";
-                    let rbms_path = ObjectPath::new(env::var("FEM")?)
-                        .join("cfd")
-                        .join(cfd_case.to_string())
-                        .join(object);
-                    gmt.windloads(clone_store, rbms_path).await?
-                };
-
-                while gmt.ray_trace_all().is_some() {
-                    pb.tick();
-                }
-                pb.finish();
-                Result::<_, anyhow::Error>::Ok((cfd_case.to_string(), gmt.compute_pssn()))
-            }));
-        }
-        mpb.clear()?;
-        for h in h {
-            let (case, pssn) = h.await??;
-            pssns.insert(case, pssn);
-        }
+            // Setup GMT optics and imaging
+            let gmt = GmtOpticalModel::builder().h_band().build()?;
+
+            let gmt = {
+                let cfd_path = ObjectPath::from(Baseline::<CFD_YEAR>::path()?.to_str().unwrap())
+                    .join(cfd_case.to_string());
+                gmt.domeseeing_with_retry(clone_store.clone(), cfd_path, &RetryPolicy::default())
+                    .await?
+            };
+
+            let mut gmt = {
+                let rbms_path = ObjectPath::new(env::var("FEM")?)
+                    .join("cfd")
+                    .join(cfd_case.to_string())
+                    .join(RbmSeries::OpenLoop.file_name());
+                gmt.windloads_with_retry(clone_store, rbms_path, &RetryPolicy::default())
+                    .await?
+            };
+
+            while gmt.ray_trace_all().is_some() {
+                pb.tick();
+            }
+            pb.finish();
+            CasePssn {
+                cfd_case: cfd_case.to_string(),
+                pssn: gmt.compute_pssn(),
+            }
+            .save()
+        }));
+    }
+    for h in h {
+        h.await??;
+    }
+    mpb.clear()?;
+
+    let mut pssns = SweepPssn::default();
+    for cfd_case in cfd_cases {
+        let CasePssn { cfd_case, pssn } = CasePssn::load(&cfd_case.to_string())?;
+        pssns.insert(cfd_case, pssn)?;
     }
     serde_pickle::to_writer(
         &mut File::create("cfd_domeseeing-windloads_h-pssn.pkl")?,
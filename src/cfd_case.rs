@@ -0,0 +1,142 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{AzimuthAngle, ElevationAngle, Enclosure, WindSpeed, ZenithAngle};
+
+/// Returns the title of a CFD case, e.g. `zen30az045_OS_7ms`
+///
+/// The title is the key of the CFD cases in the YouTube playlists;
+/// the zenith and azimuth angles are zero-padded to 2 and 3 digits, respectively.
+/// It parses back into a [CfdCaseKey].
+pub fn cfd_case_title(elevation: ElevationAngle, azimuth: AzimuthAngle, wind: WindSpeed) -> String {
+    format!(
+        "zen{:02}az{:03}_{}_{}ms",
+        ZenithAngle::from(elevation).as_u32(),
+        azimuth.as_u32(),
+        Enclosure::new(wind.as_u32(), elevation)
+            .as_str()
+            .to_uppercase(),
+        wind.as_u32()
+    )
+}
+
+/// Error parsing a CFD case name
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseCfdCaseError(pub(crate) String);
+impl Display for ParseCfdCaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid CFD case name {:?}", self.0)
+    }
+}
+impl std::error::Error for ParseCfdCaseError {}
+
+/// Sort key of a CFD case
+///
+/// CFD case names (e.g. `zen30az045_OS7`) do not sort lexicographically in a physically
+/// meaningful order, the key sorts the CFD cases by elevation, azimuth, enclosure
+/// configuration and wind speed instead.
+///
+/// The key is parsed back from the CFD case name:
+/// ```
+/// use gmt_cfd_psf::CfdCaseKey;
+///
+/// let key: CfdCaseKey = "zen30az045_OS7".parse().unwrap();
+/// assert_eq!(key.elevation, 60);
+/// assert_eq!(key.azimuth, 45);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CfdCaseKey {
+    /// Elevation angle in degrees
+    pub elevation: u32,
+    /// Azimuth angle in degrees
+    pub azimuth: u32,
+    /// Enclosure configuration (os, cd or cs)
+    pub enclosure: String,
+    /// Wind speed in m/s
+    pub wind_speed: u32,
+}
+
+impl FromStr for CfdCaseKey {
+    type Err = ParseCfdCaseError;
+
+    /// Parses CFD case names as `zen<zenith>az<azimuth>_<enclosure>[_]<wind speed>[ms]`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseCfdCaseError(s.to_string());
+        fn split_digits(s: &str) -> Option<(u32, &str)> {
+            let n = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            let (digits, rest) = s.split_at(n);
+            digits.parse::<u32>().ok().map(|value| (value, rest))
+        }
+        let rest = s.strip_prefix("zen").ok_or_else(err)?;
+        let (zenith, rest) = split_digits(rest).ok_or_else(err)?;
+        let rest = rest.strip_prefix("az").ok_or_else(err)?;
+        let (azimuth, rest) = split_digits(rest).ok_or_else(err)?;
+        let rest = rest.strip_prefix('_').ok_or_else(err)?;
+        let n = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .ok_or_else(err)?;
+        let (enclosure, rest) = rest.split_at(n);
+        let rest = rest.strip_prefix('_').unwrap_or(rest);
+        let (wind_speed, rest) = split_digits(rest).ok_or_else(err)?;
+        if !(rest.is_empty() || rest == "ms") || zenith > 90 {
+            return Err(err());
+        }
+        Ok(Self {
+            elevation: 90 - zenith,
+            azimuth,
+            enclosure: enclosure.to_lowercase(),
+            wind_speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_sort() {
+        let mut keys: Vec<CfdCaseKey> = ["zen30az090_OS7", "zen30az045_OS7", "zen00az000_cd_12ms"]
+            .into_iter()
+            .map(|case| case.parse().unwrap())
+            .collect();
+        keys.sort();
+        assert_eq!(
+            keys.iter()
+                .map(|key| (key.elevation, key.azimuth))
+                .collect::<Vec<_>>(),
+            vec![(60, 45), (60, 90), (90, 0)]
+        );
+        assert_eq!(keys[2].enclosure, "cd");
+        assert_eq!(keys[2].wind_speed, 12);
+        assert!("zen30_OS7".parse::<CfdCaseKey>().is_err());
+    }
+
+    #[test]
+    fn title_padding() {
+        assert_eq!(
+            cfd_case_title(
+                ElevationAngle::Ninety,
+                AzimuthAngle::FortyFive,
+                WindSpeed::Two
+            ),
+            "zen00az045_OS_2ms"
+        );
+        assert_eq!(
+            cfd_case_title(
+                ElevationAngle::Thirty,
+                AzimuthAngle::OneEighty,
+                WindSpeed::Twelve
+            ),
+            "zen60az180_CS_12ms"
+        );
+        let key: CfdCaseKey = cfd_case_title(
+            ElevationAngle::Sixty,
+            AzimuthAngle::Zero,
+            WindSpeed::Seventeen,
+        )
+        .parse()
+        .unwrap();
+        assert_eq!((key.elevation, key.azimuth), (60, 0));
+        assert_eq!((key.enclosure.as_str(), key.wind_speed), ("cd", 17));
+    }
+}
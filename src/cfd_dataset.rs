@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use object_store::{ObjectStore, path::Path as ObjectPath};
+use parse_monitors::{CFD_YEAR, cfd::CfdCase};
+
+use crate::CfdCaseKey;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CfdDatasetError {
+    #[error("failed to list the CFD cases in the object store")]
+    ObjectStore(#[from] object_store::Error),
+}
+
+/// Lists the CFD cases available in the CFD dataset
+///
+/// The CFD case directories right below `prefix` in the object store are parsed back
+/// into CFD cases; the directories with names that are not CFD case names
+/// or that do not match a CFD case of the dataset are skipped with a warning.
+/// The CFD cases are sorted by [CfdCaseKey].
+pub async fn list_available_cases(
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+) -> Result<Vec<CfdCase<CFD_YEAR>>, CfdDatasetError> {
+    let listing = store.list_with_delimiter(Some(&prefix)).await?;
+    let mut keys: Vec<CfdCaseKey> = listing
+        .common_prefixes
+        .iter()
+        .filter_map(|path| path.filename())
+        .filter_map(|name| {
+            name.parse()
+                .map_err(|e| log::warn!("skipping {prefix}/{name}: {e}"))
+                .ok()
+        })
+        .collect();
+    keys.sort();
+    Ok(keys
+        .into_iter()
+        .filter_map(|key| {
+            CfdCase::<CFD_YEAR>::colloquial(
+                90 - key.elevation,
+                key.azimuth,
+                key.enclosure.as_str(),
+                key.wind_speed,
+            )
+            .map_err(|_| log::warn!("skipping {key:?}: not a CFD case of year {CFD_YEAR}"))
+            .ok()
+        })
+        .collect())
+}
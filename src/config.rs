@@ -13,11 +13,22 @@ and metadata overlays for scientific visualization.
 - Builder pattern for flexible configuration
 */
 
-use std::{f32, sync::Arc};
+use std::{
+    f32,
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use ab_glyph::{FontRef, InvalidFont};
 use image::{Rgb, RgbImage};
-use imageproc::drawing::draw_text_mut;
+use imageproc::{
+    drawing::{draw_filled_rect_mut, draw_line_segment_mut, draw_text_mut},
+    rect::Rect,
+};
+
+use crate::{DETECTOR_SIZE, Enclosure};
 
 /// Errors that can occur during PSF configuration operations
 #[derive(Debug, thiserror::Error)]
@@ -25,30 +36,327 @@ pub enum ConfigError {
     /// Font loading failed
     #[error("Failed to load font")]
     Font(#[from] InvalidFont),
+    /// Failed to create or open the JSON configuration file
+    #[error("Failed to access the configuration file {1:?}")]
+    JsonFile(#[source] io::Error, PathBuf),
+    /// Failed to write the configuration as JSON
+    #[error("Failed to write the configuration to {1:?}")]
+    WriteJson(#[source] serde_json::Error, PathBuf),
+    /// Failed to read the configuration from JSON
+    #[error("Failed to read the configuration from {1:?}")]
+    ReadJson(#[source] serde_json::Error, PathBuf),
 }
 
 /// Configuration for PSF rendering including visualization parameters and metadata
 ///
-/// This type uses interior mutability through [`Arc`] to enable shared configuration
-/// across multiple PSF instances while supporting builder-pattern modifications.
+/// The configuration is immutable once built and shared as an [`Arc`] across the PSF instances:
+/// the builder methods take `self: Arc<Self>` and return a new `Arc<Config>`,
+/// leaving the configuration held by the existing PSFs unchanged.
 ///
 /// # Example
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 /// use psf::Config;
 ///
 /// let config = Config::new(50.0, 25.0, 500.0)
 ///     .cfd_case("30deg_0deg_os_7ms")  
 ///     .turbulence_effects("dome seeing + wind loads");
 /// ```
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Config {
     pub(crate) seeing_radius_pixels: f32,
     pub(crate) segment_diff_lim_radius_pixels: f32,
     pub(crate) wavelength_nm: f64,
     pub(crate) cfd_case: Option<String>,
     pub(crate) turbulence_effects: Option<String>,
+    pub(crate) model: Option<ModelMetadata>,
+    pub(crate) overlays: Vec<Overlay>,
+    pub(crate) legend: Option<AngleUnit>,
+    pub(crate) overlay_frames: OverlayFrames,
+    #[serde(skip)]
+    pub(crate) plate_scale: PlateScale,
+    pub(crate) wind_azimuth_deg: Option<f64>,
+    pub(crate) colorbar: bool,
+    pub(crate) normalization: Normalization,
+    pub(crate) colormap: Option<Colormap>,
+    pub(crate) detector_noise: Option<DetectorNoise>,
+}
+
+/// Width in pixels of the colorbar strip appended to the right of the PSF images
+pub const COLORBAR_WIDTH: u32 = 90;
+
+/// Direction the wind is blowing from in the CFD simulations (NNE), in degrees from North
+const WIND_FROM_DEG: f64 = 22.5;
+
+/// Line segment between two (x,y) image points
+type Segment = ((f32, f32), (f32, f32));
+
+/// PSSn estimator error model
+///
+/// [PSSnErrorModel::Telescope] is the PSSn of the telescope alone, whereas
+/// [PSSnErrorModel::AtmosphereTelescope] is the PSSn of the telescope
+/// including the atmospheric turbulence
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum PSSnErrorModel {
+    #[default]
+    Telescope,
+    AtmosphereTelescope,
+}
+
+/// Angular units of the overlay legend
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum AngleUnit {
+    /// Detector pixels
+    #[default]
+    Pixel,
+    /// Milli-arcseconds
+    Mas,
+    /// Arcseconds
+    Arcsec,
+}
+impl AngleUnit {
+    /// Format an angular size given in pixels into this unit
+    ///
+    /// Falls back to pixels if the pixel scale is unknown
+    pub fn format(&self, pixels: f32, pixel_scale_mas: Option<f64>) -> String {
+        self.format_angle(pixels, pixel_scale_mas.map(|px| pixels as f64 * px))
+    }
+    /// Format an angular size given both in pixels and in milli-arcseconds into this unit
+    ///
+    /// Falls back to pixels if the angular size in milli-arcseconds is unknown
+    pub fn format_angle(&self, pixels: f32, mas: Option<f64>) -> String {
+        match (self, mas) {
+            (Self::Mas, Some(mas)) => format!("{:.0}mas", mas),
+            (Self::Arcsec, Some(mas)) => format!("{:.2}arcsec", mas * 1e-3),
+            _ => format!("{:.1}px", pixels),
+        }
+    }
+}
+
+/// PSF images the circle overlays and their legend are drawn onto
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum OverlayFrames {
+    /// Both the short and the long exposure PSFs
+    #[default]
+    All,
+    /// Only the long exposure PSF, leaving the short exposure frames clean
+    LongExposure,
+    /// Only the short exposure frames
+    ShortExposure,
+}
+impl OverlayFrames {
+    /// Returns true if the overlays are drawn onto a short or a long exposure PSF
+    pub fn draws(&self, long_exposure: bool) -> bool {
+        match self {
+            Self::All => true,
+            Self::LongExposure => long_exposure,
+            Self::ShortExposure => !long_exposure,
+        }
+    }
+}
+
+/// Intensity scaling of the PSF images
+///
+/// The intensity is first normalized linearly to `[0,1]` by the frame bounds,
+/// the logarithmic and inverse hyperbolic sine stretches bring out the faint
+/// diffraction rings and seeing halo next to the bright core.
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Normalization {
+    #[default]
+    Linear,
+    /// Logarithmic stretch over 3 decades
+    Log,
+    /// Inverse hyperbolic sine stretch, linear below `softening` and logarithmic above
+    Asinh { softening: f64 },
+}
+impl Normalization {
+    /// Dynamic range of the logarithmic stretch
+    const LOG_RANGE: f64 = 1e3;
+    /// Maps a linearly normalized intensity in `[0,1]` to the colormap value in `[0,1]`
+    pub fn stretch(&self, value: f64) -> f64 {
+        let value = value.clamp(0., 1.);
+        match *self {
+            Self::Linear => value,
+            Self::Log => (1. + Self::LOG_RANGE * value).ln() / (1. + Self::LOG_RANGE).ln(),
+            Self::Asinh { softening } if softening > 0. => {
+                (value / softening).asinh() / softening.recip().asinh()
+            }
+            Self::Asinh { .. } => value,
+        }
+    }
+    /// Maps a colormap value in `[0,1]` back to the linearly normalized intensity
+    pub fn inverse(&self, value: f64) -> f64 {
+        let value = value.clamp(0., 1.);
+        match *self {
+            Self::Linear => value,
+            Self::Log => ((1. + Self::LOG_RANGE).powf(value) - 1.) / Self::LOG_RANGE,
+            Self::Asinh { softening } if softening > 0. => {
+                softening * (value * softening.recip().asinh()).sinh()
+            }
+            Self::Asinh { .. } => value,
+        }
+    }
+}
+
+/// Colormap of the PSF and OPD images
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Colormap {
+    /// Black to white
+    Gray,
+    Viridis,
+    Inferno,
+    Turbo,
+}
+impl Colormap {
+    /// Returns the RGB color of a value in `[0,1]`
+    pub fn rgb(&self, value: f64) -> [u8; 3] {
+        let value = value.clamp(0., 1.);
+        let gradient = match self {
+            Self::Gray => {
+                let gray = (255. * value).round() as u8;
+                return [gray; 3];
+            }
+            Self::Viridis => colorous::VIRIDIS,
+            Self::Inferno => colorous::INFERNO,
+            Self::Turbo => colorous::TURBO,
+        };
+        let color = gradient.eval_continuous(value);
+        [color.r, color.g, color.b]
+    }
+}
+
+/// Model of the detector plate scale used to convert radii in pixels into angles
+#[derive(Debug, Default, Clone, Copy)]
+pub enum PlateScale {
+    /// Constant plate scale: the angle is the radius times the pixel scale
+    #[default]
+    Constant,
+    /// Radially varying plate scale accounting for the field distortion
+    ///
+    /// The function maps the angle in milli-arcseconds given by the constant
+    /// pixel scale to the true angle in milli-arcseconds
+    Radial(fn(f64) -> f64),
+}
+impl PlateScale {
+    /// Converts a radius in pixels into an angle in milli-arcseconds
+    pub fn to_mas(&self, pixels: f32, pixel_scale_mas: f64) -> f64 {
+        let mas = pixels as f64 * pixel_scale_mas;
+        match self {
+            Self::Constant => mas,
+            Self::Radial(distortion) => distortion(mas),
+        }
+    }
 }
+
+/// Circle overlay drawn on top of the PSF images
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Overlay {
+    /// Legend label
+    pub label: String,
+    /// Circle radius in detector pixels
+    pub radius_pixels: f32,
+    /// Circle and legend color
+    #[serde(with = "rgb")]
+    pub color: Rgb<u8>,
+}
+
+/// (De)serialization of the overlay colors as `[r, g, b]` arrays
+mod rgb {
+    use image::Rgb;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Rgb<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        color.0.serialize(serializer)
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rgb<u8>, D::Error> {
+        <[u8; 3]>::deserialize(deserializer).map(Rgb)
+    }
+}
+impl Overlay {
+    /// Create a new white circle overlay
+    pub fn new(label: impl ToString, radius_pixels: f32) -> Self {
+        Self {
+            label: label.to_string(),
+            radius_pixels,
+            color: Rgb([255u8, 255u8, 255u8]),
+        }
+    }
+    /// Set the overlay color
+    pub fn color(self, color: Rgb<u8>) -> Self {
+        Self { color, ..self }
+    }
+}
+
+/// Detector noise added to the frames read out from the detector
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DetectorNoise {
+    /// Poisson sampling of the photo-electrons
+    pub photon: bool,
+    /// Gaussian read noise RMS in electrons
+    pub read_noise_e: f64,
+    /// Exposure time in seconds
+    pub exposure_s: f64,
+}
+
+/// Optical model settings the PSF frames have been computed with
+///
+/// This is recorded in the run metadata sidecar for provenance.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ModelMetadata {
+    /// Source photometric band
+    pub band: String,
+    /// Photometric band the PSSn is evaluated in
+    pub pssn_band: String,
+    /// Detector size in pixels
+    pub detector_size: usize,
+    /// Detector Nyquist oversampling factor
+    pub osf: usize,
+    /// Detector pixel scale in milli-arcseconds
+    pub pixel_scale_mas: f64,
+    /// Atmosphere Fried parameter in meters
+    pub atmosphere_r0: f64,
+    /// Atmosphere outer scale in meters, `None` for the CRSEO default
+    pub atmosphere_l0: Option<f64>,
+    /// Seed of the detector noise generator, `None` if seeded from the operating system
+    pub seed: Option<u64>,
+    /// Dome seeing settings, if the dome seeing has been loaded
+    pub domeseeing: Option<DomeSeeingMetadata>,
+    /// Wind loads settings, if the wind loads have been loaded
+    pub windloads: Option<WindLoadsMetadata>,
+    /// Sampling frequency of the frames in Hz
+    pub frame_rate_hz: f64,
+    /// PSSn estimator error model
+    pub pssn_error_model: PSSnErrorModel,
+    /// Whether the atmospheric seeing contribution is included
+    pub atmosphere: bool,
+}
+
+/// Dome seeing settings the PSF frames have been computed with
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DomeSeeingMetadata {
+    /// Path to the CFD case dome seeing OPDs
+    pub path: String,
+    /// Whether the dome seeing is added to the wavefront
+    pub enabled: bool,
+}
+
+/// Wind loads settings the PSF frames have been computed with
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WindLoadsMetadata {
+    /// Path to the M1 & M2 rigid body motions parquet file
+    pub path: String,
+    /// Sampling frequency of the rigid body motions in Hz
+    pub input_hz: usize,
+    /// Sampling frequency of the downsampled rigid body motions in Hz
+    pub output_hz: f64,
+    /// Duration of the skipped start of the time series in seconds
+    pub skip_seconds: f64,
+    /// Rigid body motions gain
+    pub gain: f64,
+    /// Whether the rigid body motions are applied to M1 & M2
+    pub enabled: bool,
+}
+
 impl Config {
     /// Create a new PSF configuration with core visualization parameters
     ///
@@ -66,12 +374,74 @@ impl Config {
         segment_diff_lim_radius_pixels: f32,
         wavelength_nm: f64,
     ) -> Arc<Self> {
-        Arc::new(Self {
+        let config = Self {
             seeing_radius_pixels,
             segment_diff_lim_radius_pixels,
             wavelength_nm,
+            overlays: vec![
+                Overlay::new("seeing radius", seeing_radius_pixels),
+                Overlay::new("segment diff. limit radius", segment_diff_lim_radius_pixels),
+            ],
             ..Default::default()
-        })
+        };
+        config.check_overlays();
+        Arc::new(config)
+    }
+
+    /// Saves the configuration to a JSON file
+    ///
+    /// The configuration is restored with [Config::from_json],
+    /// except for the [PlateScale] that is reset to [PlateScale::Constant]
+    pub fn to_json(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| ConfigError::JsonFile(e, path.to_path_buf()))?;
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|e| ConfigError::WriteJson(e, path.to_path_buf()))
+    }
+
+    /// Loads a configuration saved with [Config::to_json]
+    pub fn from_json(path: impl AsRef<Path>) -> Result<Arc<Self>, ConfigError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| ConfigError::JsonFile(e, path.to_path_buf()))?;
+        let config: Self = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| ConfigError::ReadJson(e, path.to_path_buf()))?;
+        Ok(Arc::new(config))
+    }
+
+    /// Ratio of the detector field-of-view to the atmospheric seeing diameter
+    ///
+    /// A ratio smaller than 1 means that the seeing circle overlay is clipped
+    /// by the edges of the detector, which happens for strong seeing at short
+    /// wavelengths; the detector should then be enlarged.
+    pub fn fov_seeing_ratio(&self) -> f32 {
+        self.detector_size() as f32 / (2. * self.seeing_radius_pixels)
+    }
+
+    /// Detector size in pixels of the optical model or [DETECTOR_SIZE] if unknown
+    pub fn detector_size(&self) -> usize {
+        self.model
+            .as_ref()
+            .map_or(DETECTOR_SIZE, |model| model.detector_size)
+    }
+
+    /// Log a warning for each circle overlay that does not fit in the detector
+    ///
+    /// # Returns
+    ///
+    /// The ratio of the detector field-of-view to the atmospheric seeing diameter
+    pub fn check_overlays(&self) -> f32 {
+        let half_detector = self.detector_size() as f32 / 2.;
+        for overlay in &self.overlays {
+            if overlay.radius_pixels > half_detector {
+                log::warn!(
+                    "the {} circle ({:.1}px) exceeds half the detector size ({}px) and will be clipped",
+                    overlay.label,
+                    overlay.radius_pixels,
+                    half_detector
+                );
+            }
+        }
+        self.fov_seeing_ratio()
     }
 
     /// Add CFD case information to be displayed in text overlay
@@ -84,19 +454,9 @@ impl Config {
     ///
     /// New configuration instance with CFD case metadata
     pub fn cfd_case(self: Arc<Self>, value: impl ToString) -> Arc<Self> {
-        let &Self {
-            seeing_radius_pixels,
-            segment_diff_lim_radius_pixels,
-            wavelength_nm,
-            ref turbulence_effects,
-            ..
-        } = &*self;
         Arc::new(Self {
-            seeing_radius_pixels,
-            segment_diff_lim_radius_pixels,
-            wavelength_nm,
             cfd_case: Some(value.to_string()),
-            turbulence_effects: turbulence_effects.clone(),
+            ..(*self).clone()
         })
     }
 
@@ -110,33 +470,486 @@ impl Config {
     ///
     /// New configuration instance with turbulence effects metadata
     pub fn turbulence_effects(self: Arc<Self>, value: impl ToString) -> Arc<Self> {
-        let &Self {
-            seeing_radius_pixels,
-            segment_diff_lim_radius_pixels,
-            wavelength_nm,
-            ref cfd_case,
-            ..
-        } = &*self;
         Arc::new(Self {
-            seeing_radius_pixels,
-            segment_diff_lim_radius_pixels,
-            wavelength_nm,
-            cfd_case: cfd_case.clone(),
             turbulence_effects: Some(value.to_string()),
+            ..(*self).clone()
+        })
+    }
+
+    /// Attach the optical model settings the frames have been computed with
+    ///
+    /// # Parameters
+    ///
+    /// - `value` - Optical model settings recorded in the run metadata
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with optical model metadata
+    pub fn model(self: Arc<Self>, value: ModelMetadata) -> Arc<Self> {
+        Arc::new(Self {
+            model: Some(value),
+            ..(*self).clone()
+        })
+    }
+
+    /// Set the plate scale model used to convert the overlay radii into angles
+    ///
+    /// # Parameters
+    ///
+    /// - `value` - Constant (default) or radially varying plate scale
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the plate scale model
+    pub fn plate_scale_model(self: Arc<Self>, value: PlateScale) -> Arc<Self> {
+        Arc::new(Self {
+            plate_scale: value,
+            ..(*self).clone()
+        })
+    }
+
+    /// Convert a radius in pixels into an angle in milli-arcseconds
+    ///
+    /// Returns `None` if the pixel scale is unknown
+    pub fn radius_mas(&self, pixels: f32) -> Option<f64> {
+        self.model
+            .as_ref()
+            .map(|model| self.plate_scale.to_mas(pixels, model.pixel_scale_mas))
+    }
+
+    /// Rescale the configuration for detector frames binned by `bin`×`bin` pixels
+    ///
+    /// The overlay radii and the detector size are divided by `bin`
+    /// and the pixel scale is multiplied by `bin`
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance for the binned frames
+    pub fn binned(self: Arc<Self>, bin: usize) -> Arc<Self> {
+        let b = bin as f32;
+        Arc::new(Self {
+            seeing_radius_pixels: self.seeing_radius_pixels / b,
+            segment_diff_lim_radius_pixels: self.segment_diff_lim_radius_pixels / b,
+            model: self.model.clone().map(|model| ModelMetadata {
+                detector_size: model.detector_size / bin,
+                pixel_scale_mas: model.pixel_scale_mas * bin as f64,
+                ..model
+            }),
+            overlays: self
+                .overlays
+                .iter()
+                .cloned()
+                .map(|overlay| Overlay {
+                    radius_pixels: overlay.radius_pixels / b,
+                    ..overlay
+                })
+                .collect(),
+            ..(*self).clone()
+        })
+    }
+
+    /// Rescale the configuration for frames oversampled by `factor` on each axis
+    ///
+    /// The overlay radii and the detector size are multiplied by `factor`
+    /// and the pixel scale is divided by `factor`
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance for the oversampled frames
+    pub fn oversampled(self: Arc<Self>, factor: usize) -> Arc<Self> {
+        let s = factor as f32;
+        Arc::new(Self {
+            seeing_radius_pixels: self.seeing_radius_pixels * s,
+            segment_diff_lim_radius_pixels: self.segment_diff_lim_radius_pixels * s,
+            model: self.model.clone().map(|model| ModelMetadata {
+                detector_size: model.detector_size * factor,
+                pixel_scale_mas: model.pixel_scale_mas / factor as f64,
+                ..model
+            }),
+            overlays: self
+                .overlays
+                .iter()
+                .cloned()
+                .map(|overlay| Overlay {
+                    radius_pixels: overlay.radius_pixels * s,
+                    ..overlay
+                })
+                .collect(),
+            ..(*self).clone()
+        })
+    }
+
+    /// Replace the circle overlays
+    ///
+    /// By default, the overlays are the atmospheric seeing and
+    /// the GMT segment diffraction limit circles
+    ///
+    /// # Parameters
+    ///
+    /// - `value` - Circle overlays
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the given overlays
+    pub fn overlays(self: Arc<Self>, value: Vec<Overlay>) -> Arc<Self> {
+        let config = Self {
+            overlays: value,
+            ..(*self).clone()
+        };
+        config.check_overlays();
+        Arc::new(config)
+    }
+
+    /// Draw a legend of the circle overlays in the bottom left corner of the image
+    ///
+    /// # Parameters
+    ///
+    /// - `value` - Units the overlay radii are written in
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the overlay legend
+    pub fn legend(self: Arc<Self>, value: AngleUnit) -> Arc<Self> {
+        Arc::new(Self {
+            legend: Some(value),
+            ..(*self).clone()
+        })
+    }
+
+    /// Select the PSF images the circle overlays are drawn onto
+    ///
+    /// # Parameters
+    ///
+    /// - `value` - Short and/or long exposure PSFs
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the overlay selection
+    pub fn overlay_frames(self: Arc<Self>, value: OverlayFrames) -> Arc<Self> {
+        Arc::new(Self {
+            overlay_frames: value,
+            ..(*self).clone()
+        })
+    }
+
+    /// Draw an arrow of the wind direction in the top right corner of the image
+    ///
+    /// In the CFD simulations, the wind is always blowing from the NNE direction.
+    /// The arrow shows the direction the wind is blowing to relative to the telescope
+    /// oriented at the given azimuth angle, the top of the image pointing to the
+    /// azimuth of the telescope.
+    ///
+    /// # Parameters
+    ///
+    /// - `azimuth_deg` - Telescope azimuth angle in degrees
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the wind direction arrow
+    pub fn wind_direction(self: Arc<Self>, azimuth_deg: f64) -> Arc<Self> {
+        Arc::new(Self {
+            wind_azimuth_deg: Some(azimuth_deg),
+            ..(*self).clone()
+        })
+    }
+
+    /// Append a colorbar to the right of the PSF images
+    ///
+    /// # Parameters
+    ///
+    /// - `value` - Whether the colorbar is shown
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the colorbar setting
+    pub fn show_colorbar(self: Arc<Self>, value: bool) -> Arc<Self> {
+        Arc::new(Self {
+            colorbar: value,
+            ..(*self).clone()
+        })
+    }
+
+    /// Set the intensity scaling of the PSF images
+    ///
+    /// # Parameters
+    ///
+    /// - `normalization` - Linear, logarithmic or inverse hyperbolic sine scaling
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the intensity scaling
+    pub fn normalization(self: Arc<Self>, normalization: Normalization) -> Arc<Self> {
+        Arc::new(Self {
+            normalization,
+            ..(*self).clone()
+        })
+    }
+
+    /// Add photon and read noise to the frames read out from the detector
+    ///
+    /// The detector frames are scaled to photo-electrons by the exposure time,
+    /// Poisson sampled if `photon` is true and Gaussian read noise is added.
+    /// The frames are noiseless by default.
+    ///
+    /// # Parameters
+    ///
+    /// - `photon` - Whether the photon noise is applied
+    /// - `read_noise_e` - Read noise RMS in electrons
+    /// - `exposure_s` - Exposure time in seconds
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the detector noise
+    pub fn detector_noise(
+        self: Arc<Self>,
+        photon: bool,
+        read_noise_e: f64,
+        exposure_s: f64,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            detector_noise: Some(DetectorNoise {
+                photon,
+                read_noise_e,
+                exposure_s,
+            }),
+            ..(*self).clone()
+        })
+    }
+
+    /// Set the colormap of both the PSF and the OPD images
+    ///
+    /// The PSF images default to the CUBEHELIX colormap and the OPD maps
+    /// to the diverging SPECTRAL colormap
+    ///
+    /// # Parameters
+    ///
+    /// - `colormap` - Colormap of the images
+    ///
+    /// # Returns
+    ///
+    /// New configuration instance with the colormap
+    pub fn colormap(self: Arc<Self>, colormap: Colormap) -> Arc<Self> {
+        Arc::new(Self {
+            colormap: Some(colormap),
+            ..(*self).clone()
         })
     }
+
+    /// Returns the RGB color of a normalized PSF intensity in `[0,1]`
+    pub(crate) fn psf_rgb(&self, value: f64) -> [u8; 3] {
+        match self.colormap {
+            Some(colormap) => colormap.rgb(value),
+            None => {
+                let color = colorous::CUBEHELIX.eval_continuous(value);
+                [color.r, color.g, color.b]
+            }
+        }
+    }
+
+    /// Returns the RGB color of a normalized signed PSF intensity in `[0,1]`, 0.5 being zero
+    ///
+    /// The colormap is the diverging RED_BLUE colormap whatever the [Colormap]
+    /// of the configuration
+    pub(crate) fn signed_rgb(&self, value: f64) -> [u8; 3] {
+        let color = colorous::RED_BLUE.eval_continuous(value);
+        [color.r, color.g, color.b]
+    }
+
+    /// Returns the RGB color of a normalized OPD value in `[0,1]`
+    pub(crate) fn opd_rgb(&self, value: f64) -> [u8; 3] {
+        match self.colormap {
+            Some(colormap) => colormap.rgb(value),
+            None => {
+                let color = colorous::SPECTRAL.eval_continuous(value);
+                [color.r, color.g, color.b]
+            }
+        }
+    }
+
+    /// Line segments of the wind direction arrow: the shaft and both sides of the head
+    fn wind_arrow(&self, width: u32) -> Option<[Segment; 3]> {
+        let azimuth = self.wind_azimuth_deg?;
+        let length = 40f32;
+        let (cx, cy) = (width as f32 - 35., 45.);
+        // direction the wind is blowing to, clockwise from the top of the image
+        let to = ((WIND_FROM_DEG - azimuth + 180.) as f32).to_radians();
+        let (ux, uy) = (to.sin(), -to.cos());
+        let tail = (cx - 0.5 * length * ux, cy - 0.5 * length * uy);
+        let tip = (cx + 0.5 * length * ux, cy + 0.5 * length * uy);
+        let head = |angle: f32| {
+            let (s, c) = (angle.sin(), angle.cos());
+            let (hx, hy) = (-ux * c + uy * s, -ux * s - uy * c);
+            (tip.0 + 0.3 * length * hx, tip.1 + 0.3 * length * hy)
+        };
+        let a = 25f32.to_radians();
+        Some([(tail, tip), (tip, head(a)), (tip, head(-a))])
+    }
+
+    /// Draw the wind direction arrow in the top right corner of the image
+    ///
+    /// Nothing is drawn if the wind direction is not set, see [Config::wind_direction]
+    ///
+    /// # Parameters
+    ///
+    /// - `image` - Mutable reference to RGB image to modify
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or font loading error
+    pub fn draw_wind_arrow(&self, image: &mut RgbImage) -> Result<(), ConfigError> {
+        let Some(segments) = self.wind_arrow(image.width()) else {
+            return Ok(());
+        };
+        let white = Rgb([255u8, 255u8, 255u8]);
+        for (start, end) in segments {
+            draw_line_segment_mut(image, start, end, white);
+        }
+        let x = image.width() as i32 - 50;
+        self.draw_label(image, (x, 70), 15., "wind")
+    }
+
+    /// Draw a badge of the enclosure configuration in the bottom right corner of the image
+    ///
+    /// The badge is labeled OS, CD or CS on a green, orange or blue background, respectively
+    ///
+    /// # Parameters
+    ///
+    /// - `image` - Mutable reference to RGB image to modify
+    /// - `enclosure` - Enclosure configuration
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or font loading error
+    pub fn draw_enclosure_badge(
+        &self,
+        image: &mut RgbImage,
+        enclosure: Enclosure,
+    ) -> Result<(), ConfigError> {
+        let color = match enclosure {
+            Enclosure::OpenSky => Rgb([46u8, 160u8, 67u8]),
+            Enclosure::ClosedDome => Rgb([230u8, 126u8, 34u8]),
+            Enclosure::ClosedSky => Rgb([41u8, 98u8, 255u8]),
+        };
+        let (width, height) = (26u32, 16u32);
+        let x = image.width().saturating_sub(width + 2) as i32;
+        let y = image.height().saturating_sub(height + 2) as i32;
+        draw_filled_rect_mut(image, Rect::at(x, y).of_size(width, height), color);
+        self.draw_label(
+            image,
+            (x + 3, y + 1),
+            13.,
+            &enclosure.as_str().to_uppercase(),
+        )
+    }
+
+    /// Append a vertical colorbar strip to the right of the image
+    ///
+    /// The strip is [COLORBAR_WIDTH] pixels wide, the PSF colormap goes from
+    /// `min` at the bottom to `max` at the top and the ticks are labeled with the
+    /// intensity at the bottom, middle and top of the colorbar, accounting for the
+    /// [Normalization] of the image.
+    /// The image is returned unchanged if the colorbar is not enabled, see [Config::show_colorbar]
+    ///
+    /// # Parameters
+    ///
+    /// - `image` - RGB image of the PSF
+    /// - `(min, max)` - Intensity range the image colors are normalized to
+    ///
+    /// # Returns
+    ///
+    /// Result with the widened image or font loading error
+    pub fn append_colorbar(
+        &self,
+        image: RgbImage,
+        minmax: (f32, f32),
+    ) -> Result<RgbImage, ConfigError> {
+        self.append_any_colorbar(image, minmax, false)
+    }
+
+    /// Same as [Config::append_colorbar] but with the diverging colormap of signed frames
+    /// and without [Normalization], see [PSF::diff](crate::PSF::diff)
+    pub(crate) fn append_signed_colorbar(
+        &self,
+        image: RgbImage,
+        minmax: (f32, f32),
+    ) -> Result<RgbImage, ConfigError> {
+        self.append_any_colorbar(image, minmax, true)
+    }
+
+    fn append_any_colorbar(
+        &self,
+        image: RgbImage,
+        (min, max): (f32, f32),
+        signed: bool,
+    ) -> Result<RgbImage, ConfigError> {
+        if !self.colorbar {
+            return Ok(image);
+        }
+        let (width, height) = image.dimensions();
+        let mut canvas = RgbImage::new(width + COLORBAR_WIDTH, height);
+        image::imageops::replace(&mut canvas, &image, 0, 0);
+
+        let (bar_width, margin) = (16u32, 10u32);
+        let x0 = width + 6;
+        let bar_height = height.saturating_sub(2 * margin).max(2);
+        for y in 0..bar_height {
+            let value = 1. - y as f64 / (bar_height - 1) as f64;
+            let color = Rgb(if signed {
+                self.signed_rgb(value)
+            } else {
+                self.psf_rgb(value)
+            });
+            for x in x0..x0 + bar_width {
+                canvas.put_pixel(x, margin + y, color);
+            }
+        }
+
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let x_tick = (x0 + bar_width) as f32;
+        for i in 0..3 {
+            let fraction = i as f32 / 2.;
+            let y = (margin + bar_height - 1) as f32 - fraction * (bar_height - 1) as f32;
+            draw_line_segment_mut(&mut canvas, (x_tick, y), (x_tick + 4., y), white);
+            let value = if signed {
+                fraction
+            } else {
+                self.normalization.inverse(fraction as f64) as f32
+            };
+            let label = format!("{:.2e}", min + value * (max - min));
+            self.draw_label(
+                &mut canvas,
+                (x_tick as i32 + 6, (y as i32 - 6).max(0)),
+                12.,
+                &label,
+            )?;
+        }
+        Ok(canvas)
+    }
+
+    /// Get the detector noise settings, if any
+    pub fn get_detector_noise(&self) -> Option<&DetectorNoise> {
+        self.detector_noise.as_ref()
+    }
+
+    /// Get the optical model settings, if any
+    pub fn model_metadata(&self) -> Option<&ModelMetadata> {
+        self.model.as_ref()
+    }
+
     /// Draw PSSN and metadata text overlays in the top left corner of the image
     ///
     /// Renders white text overlays including:
     /// - CFD case information (if configured)
     /// - Turbulence effects description (if configured)
     /// - PSSN value with wavelength
+    /// - Strehl ratio (if provided)
     /// - Frame number (if provided)
     ///
     /// # Parameters
     ///
     /// - `image` - Mutable reference to RGB image to modify
     /// - `pssn_value` - Point Spread Function Strehl Number value to display
+    /// - `strehl_value` - Optional Strehl ratio to display
     /// - `frame_number` - Optional frame number for animated sequences
     ///
     /// # Returns
@@ -146,6 +959,7 @@ impl Config {
         &self,
         image: &mut RgbImage,
         pssn_value: f64,
+        strehl_value: Option<f64>,
         frame_number: Option<usize>,
     ) -> Result<(), ConfigError> {
         // Use system default font (typically DejaVu Sans on Linux)
@@ -159,33 +973,201 @@ impl Config {
         let x = 5i32;
         let mut y = 5i32;
 
-        // Draw CFD case if provided
-        if let Some(case) = &self.cfd_case {
-            let cfd_text = format!("{}", case);
-            draw_text_mut(image, white, x, y, scale, &font, &cfd_text);
+        for text in self.pssn_text_lines(pssn_value, strehl_value, frame_number) {
+            draw_text_mut(image, white, x, y, scale, &font, &text);
             y += 30;
         }
 
-        // Draw turbulence effects if provided
+        Ok(())
+    }
+    /// Text lines of the PSSN and metadata overlay
+    ///
+    /// The lines are, in order:
+    /// - CFD case information (if configured)
+    /// - Turbulence effects description (if configured)
+    /// - PSSN value with wavelength
+    /// - Strehl ratio (if provided)
+    /// - Frame number (if provided)
+    fn pssn_text_lines(
+        &self,
+        pssn_value: f64,
+        strehl_value: Option<f64>,
+        frame_number: Option<usize>,
+    ) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(case) = &self.cfd_case {
+            lines.push(case.to_string());
+        }
         if let Some(effects) = &self.turbulence_effects {
-            let effects_text = format!("{}", effects);
-            draw_text_mut(image, white, x, y, scale, &font, &effects_text);
-            y += 30;
+            lines.push(effects.to_string());
+        }
+        lines.push(format!(
+            "PSSN@{:.0}nm: {:.5}",
+            self.wavelength_nm, pssn_value
+        ));
+        if let Some(strehl) = strehl_value {
+            lines.push(format!("Strehl: {:.3}", strehl));
+        }
+        if let Some(frame_num) = frame_number {
+            lines.push(format!("frame {:03}", frame_num));
+        }
+        lines
+    }
+    /// Build an SVG overlay layer with the same annotations than the PNG images
+    ///
+    /// The layer has a transparent background and uses the image pixel coordinates:
+    /// it holds the circle overlays and their labels, if drawn onto this exposure type
+    /// (see [Config::overlay_frames]), and, if a PSSN value is given, the PSSN and metadata text.
+    ///
+    /// # Parameters
+    ///
+    /// - `width`, `height` - Image size in pixels
+    /// - `center` - Pixel coordinates of the optical axis the circle overlays are centered on
+    /// - `long_exposure` - Whether the image is a long exposure PSF
+    /// - `pssn_value` - Optional PSSN value to display
+    /// - `strehl_value` - Optional Strehl ratio to display with the PSSN value
+    /// - `frame_number` - Optional frame number for animated sequences
+    ///
+    /// # Returns
+    ///
+    /// SVG document
+    pub fn svg_overlay(
+        &self,
+        (width, height): (u32, u32),
+        center: (i32, i32),
+        long_exposure: bool,
+        pssn_value: Option<f64>,
+        strehl_value: Option<f64>,
+        frame_number: Option<usize>,
+    ) -> String {
+        let hex = |Rgb([r, g, b]): Rgb<u8>| format!("#{:02x}{:02x}{:02x}", r, g, b);
+        let (cx, cy) = center;
+        let unit = self.legend.unwrap_or_default();
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="DejaVu Sans">"#
+        );
+        svg.push('\n');
+        if self.overlay_frames.draws(long_exposure) {
+            for overlay in &self.overlays {
+                svg.push_str(&format!(
+                    r#"  <circle cx="{cx}" cy="{cy}" r="{}" fill="none" stroke="{}"/>"#,
+                    overlay.radius_pixels as i32,
+                    hex(overlay.color)
+                ));
+                svg.push('\n');
+            }
+            let mut y = height as i32 - 20 * self.overlays.len() as i32 - 5;
+            for overlay in &self.overlays {
+                svg.push_str(&format!(
+                    r#"  <text x="5" y="{}" font-size="15" fill="{}">{}: {}</text>"#,
+                    y + 15,
+                    hex(overlay.color),
+                    escape_xml(&overlay.label),
+                    unit.format_angle(
+                        overlay.radius_pixels,
+                        self.radius_mas(overlay.radius_pixels)
+                    )
+                ));
+                svg.push('\n');
+                y += 20;
+            }
+        }
+        if let Some(segments) = self.wind_arrow(width) {
+            for ((x1, y1), (x2, y2)) in segments {
+                svg.push_str(&format!(
+                    r#"  <line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="white"/>"#
+                ));
+                svg.push('\n');
+            }
+            svg.push_str(&format!(
+                r#"  <text x="{}" y="85" font-size="15" fill="white">wind</text>"#,
+                width as i32 - 50
+            ));
+            svg.push('\n');
         }
+        if let Some(pssn) = pssn_value {
+            let mut y = 5;
+            for text in self.pssn_text_lines(pssn, strehl_value, frame_number) {
+                svg.push_str(&format!(
+                    r#"  <text x="5" y="{}" font-size="20" fill="white">{}</text>"#,
+                    y + 20,
+                    escape_xml(&text)
+                ));
+                svg.push('\n');
+                y += 30;
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+    /// Draw the circle overlays legend in the bottom left corner of the image
+    ///
+    /// Each overlay is listed with its label and radius in the legend units,
+    /// written in the overlay color.
+    /// Nothing is drawn if the legend is not enabled.
+    ///
+    /// # Parameters
+    ///
+    /// - `image` - Mutable reference to RGB image to modify
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or font loading error
+    pub fn draw_legend(&self, image: &mut RgbImage) -> Result<(), ConfigError> {
+        let Some(unit) = self.legend else {
+            return Ok(());
+        };
+        let font_data: &[u8] = include_bytes!("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf");
+        let font = FontRef::try_from_slice(font_data)?;
 
-        // Draw PSSN text
-        let pssn_text = format!("PSSN@{:.0}nm: {:.5}", self.wavelength_nm, pssn_value);
-        draw_text_mut(image, white, x, y, scale, &font, &pssn_text);
+        let scale = 15.0;
 
-        // Draw frame number if provided
-        if let Some(frame_num) = frame_number {
-            y += 30; // Move down for next line
-            let frame_text = format!("frame {:03}", frame_num);
-            draw_text_mut(image, white, x, y, scale, &font, &frame_text);
+        // Position in bottom left corner with some padding
+        let x = 5i32;
+        let mut y = image.height() as i32 - 20 * self.overlays.len() as i32 - 5;
+
+        for overlay in &self.overlays {
+            let text = format!(
+                "{}: {}",
+                overlay.label,
+                unit.format_angle(
+                    overlay.radius_pixels,
+                    self.radius_mas(overlay.radius_pixels)
+                )
+            );
+            draw_text_mut(image, overlay.color, x, y, scale, &font, &text);
+            y += 20;
         }
 
         Ok(())
     }
+    /// Draw a single line of text at the given position of the image
+    ///
+    /// # Parameters
+    ///
+    /// - `image` - Mutable reference to RGB image to modify
+    /// - `position` - (x,y) pixel coordinates of the top left corner of the text
+    /// - `scale` - Font size in pixels
+    /// - `text` - Text to draw in white
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or font loading error
+    pub fn draw_label(
+        &self,
+        image: &mut RgbImage,
+        position: (i32, i32),
+        scale: f32,
+        text: &str,
+    ) -> Result<(), ConfigError> {
+        let font_data: &[u8] = include_bytes!("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf");
+        let font = FontRef::try_from_slice(font_data)?;
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let (x, y) = position;
+        draw_text_mut(image, white, x, y, scale, &font, text);
+        Ok(())
+    }
     pub fn draw_opd_text(
         &self,
         image: &mut RgbImage,
@@ -205,14 +1187,14 @@ impl Config {
 
         // Draw CFD case if provided
         if let Some(case) = &self.cfd_case {
-            let cfd_text = format!("{}", case);
+            let cfd_text = case.to_string();
             draw_text_mut(image, text_color, x, y, scale, &font, &cfd_text);
             y += 20;
         }
 
         // Draw turbulence effects if provided
         if let Some(effects) = &self.turbulence_effects {
-            let effects_text = format!("{}", effects);
+            let effects_text = effects.to_string();
             draw_text_mut(image, text_color, x, y, scale, &font, &effects_text);
             y += 20;
         }
@@ -253,3 +1235,11 @@ impl Config {
         Ok(())
     }
 }
+
+/// Escape the XML special characters
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
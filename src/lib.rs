@@ -9,12 +9,20 @@ from GMT CFD data using CRSEO optical modeling.
 - [`Config`] - Configuration for PSF rendering with metadata overlays
 - [`PSF`] - Individual PSF frame with associated metadata
 - [`PSFs`] - Collection of PSF frames with batch processing capabilities
+- [`RunConfig`] - CFD case and turbulence effects of a run
+
+## Configuration ownership
+
+The [`Config`] is immutable once built and shared as an `Arc<Config>`:
+its builder methods return a new `Arc<Config>` and each [`PSF`] and [`PSFs`]
+holds a clone of the `Arc` given to its constructor.
+Cloning the configuration is cheap and the PSF types are `Send` and `Sync`,
+they can be moved to other threads or tokio tasks, e.g. to encode the frames in parallel.
 
 ## Usage
 
-```rust,no_run
+```rust,ignore
 use psf::{Config, PSF, PSFs, DETECTOR_SIZE};
-use std::rc::Rc;
 
 // Create configuration
 let config = Config::new(seeing_radius, diff_limit_radius, wavelength_nm)
@@ -35,15 +43,81 @@ psfs.save_all_frames()?;
 /// Default detector size in pixels (760x760)
 pub const DETECTOR_SIZE: usize = 760;
 
+mod cfd_case;
+mod run_config;
+mod stopwatch;
+pub use cfd_case::{CfdCaseKey, ParseCfdCaseError, cfd_case_title};
+pub use run_config::{RbmSeries, RunConfig};
+pub use stopwatch::Stopwatch;
+
 cfg_if::cfg_if! {
     if #[cfg(feature="ssr")] {
-        use std::fmt::Display;
-        mod config;
+        mod cfd_dataset;
         mod optical_model;
+        pub use cfd_dataset::{CfdDatasetError, list_available_cases};
+        pub use optical_model::{
+            BANDS, Convergence, Frames, GmtOpticalModel, GmtOpticalModelError, RetryPolicy,
+            WindLoads,
+        };
+
+        impl OpticalModel for GmtOpticalModel {
+            fn ray_trace(&mut self) -> &mut Self {
+                GmtOpticalModel::ray_trace(self)
+            }
+            type Error = GmtOpticalModelError;
+            fn read_detector(&mut self) -> Result<PSF, Self::Error> {
+                GmtOpticalModel::read_detector(self)
+            }
+            fn compute_pssn(&mut self) -> f64 {
+                GmtOpticalModel::compute_pssn(self)
+            }
+            fn get_opd(&self) -> &[f32] {
+                GmtOpticalModel::get_opd(self)
+            }
+            fn get_config(&self) -> Arc<Config> {
+                GmtOpticalModel::get_config(self)
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature="cpu")] {
+        use std::{fmt::Display, sync::Arc};
+        mod config;
+        mod mock;
         mod psfs;
-        pub use config::Config;
-        pub use optical_model::GmtOpticalModel;
-        pub use psfs::{PSF, PSFs};
+        pub use config::{
+            AngleUnit, COLORBAR_WIDTH, Colormap, Config, DetectorNoise, DomeSeeingMetadata,
+            ModelMetadata, Normalization, Overlay, OverlayFrames, PSSnErrorModel, PlateScale,
+            WindLoadsMetadata,
+        };
+        pub use mock::MockOpticalModel;
+        pub use psfs::{
+            LongExposure, MtfData, N_ZERNIKE, NormMode, OpdStats, PSF, PSFError, PSFs,
+            RunMetadata,
+        };
+
+        /// Interface of the optical models generating the PSF frames
+        ///
+        /// [GmtOpticalModel] is the CRSEO/CUDA implementation and [MockOpticalModel]
+        /// is a CPU implementation producing deterministic synthetic frames for tests.
+        pub trait OpticalModel {
+            /// Propagates the source through the optical model up to the detector
+            fn ray_trace(&mut self) -> &mut Self;
+            /// Error reading out the detector
+            type Error: std::error::Error + Send + Sync + 'static;
+            /// Reads out and resets the detector
+            ///
+            /// An error is returned if the frame is empty, e.g. if the source has not been ray traced
+            fn read_detector(&mut self) -> Result<PSF, Self::Error>;
+            /// Returns the PSSn cumulated over all the frames ray traced so far
+            fn compute_pssn(&mut self) -> f64;
+            /// Returns the wavefront OPD map
+            fn get_opd(&self) -> &[f32];
+            /// Returns the PSF rendering configuration
+            fn get_config(&self) -> Arc<Config>;
+        }
 
         pub trait StorePath {
             fn new(path: impl Into<Self>) -> Self
@@ -148,6 +222,16 @@ impl From<ElevationAngle> for ZenithAngle {
     }
 }
 
+impl From<ZenithAngle> for ElevationAngle {
+    fn from(value: ZenithAngle) -> Self {
+        match value {
+            ZenithAngle::Zero => ElevationAngle::Ninety,
+            ZenithAngle::Thirty => ElevationAngle::Sixty,
+            ZenithAngle::Sixty => ElevationAngle::Thirty,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -235,21 +319,48 @@ impl WindSpeed {
     }
 }
 
+/// Enclosure configuration of the CFD cases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Enclosure {
+    /// Open vents and stowed wind screen
+    OpenSky,
+    /// Closed vents and deployed wind screen
+    ClosedDome,
+    /// Closed vents and stowed wind screen
+    ClosedSky,
+}
+
+impl Enclosure {
+    /// Determine enclosure configuration based on wind speed and zenith angle
+    pub fn new(wind_speed: u32, telescope_pointing: impl Into<ZenithAngle>) -> Self {
+        if wind_speed <= 7 {
+            Self::OpenSky // open sky for wind <= 7 m/s
+        } else if telescope_pointing.into().as_u32() < 60 {
+            Self::ClosedDome // closed dome for wind > 7 m/s and zenith < 60°
+        } else {
+            Self::ClosedSky // closed sky for wind > 7 m/s and zenith >= 60°
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OpenSky => "os",
+            Self::ClosedDome => "cd",
+            Self::ClosedSky => "cs",
+        }
+    }
+}
+
 /// Determine enclosure configuration based on wind speed and zenith angle
 pub fn get_enclosure_config(
     wind_speed: u32,
     telescope_pointing: impl Into<ZenithAngle>,
 ) -> &'static str {
-    if wind_speed <= 7 {
-        "os" // open sky for wind <= 7 m/s
-    } else if telescope_pointing.into().as_u32() < 60 {
-        "cd" // closed dome for wind > 7 m/s and zenith < 60°
-    } else {
-        "cs" // closed sky for wind > 7 m/s and zenith >= 60°
-    }
+    Enclosure::new(wind_speed, telescope_pointing).as_str()
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "ssr"))]
 mod tests {
     use std::error::Error;
 
@@ -257,6 +368,12 @@ mod tests {
 
     use crate::GmtOpticalModel;
 
+    #[test]
+    fn gmt_optical_model_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<GmtOpticalModel>();
+    }
+
     #[test]
     fn gmt_segment_identification() -> Result<(), Box<dyn Error>> {
         let mut gmt = GmtOpticalModel::new()?;
@@ -267,7 +384,7 @@ mod tests {
             vec![0.; 3].as_slice(),
             &[0f64, 1f64.from_arcsec(), 0f64, 0f64],
         );
-        let psf = gmt.ray_trace().read_detector().opd(gmt.get_opd());
+        let psf = gmt.ray_trace().read_detector()?.opd(gmt.get_opd());
         psf.save_opd_as_png("gmt_segment_identification.png", None)?;
         Ok(())
     }
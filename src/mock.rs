@@ -0,0 +1,130 @@
+/*!
+# Mock Optical Model
+
+This module provides the [`MockOpticalModel`] type, a CPU stand-in for
+[`GmtOpticalModel`](crate::GmtOpticalModel) that generates deterministic
+synthetic PSF frames, so the PSF rendering and analysis code can be tested
+without a GPU.
+*/
+
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{Config, DETECTOR_SIZE, OpticalModel, PSF, PSFError};
+
+/// Size in pixels of the mock OPD map
+const OPD_SIZE: usize = 64;
+
+/// CPU optical model producing 2D Gaussian PSFs
+///
+/// The Gaussian core wanders around the center of the detector along a circle
+/// of radius `wander` pixels, completing a revolution every `period` frames.
+/// The PSSn is the mean over the ray traced frames of the overlap between the
+/// centered and the displaced Gaussian.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use gmt_cfd_psf::{MockOpticalModel, OpticalModel, PSFs};
+///
+/// let mut model = MockOpticalModel::new(64, 4.);
+/// let mut psfs = PSFs::new(&model.get_config());
+/// for _ in 0..10 {
+///     let psf = model.ray_trace().read_detector()?;
+///     let pssn = model.compute_pssn();
+///     psfs.push(psf.pssn_value(pssn));
+/// }
+/// assert_eq!(psfs.len(), 10);
+/// # Ok::<(), gmt_cfd_psf::PSFError>(())
+/// ```
+#[derive(Debug)]
+pub struct MockOpticalModel {
+    n_px: usize,
+    sigma: f64,
+    wander: f64,
+    period: usize,
+    step: usize,
+    frame: Vec<f32>,
+    pssn: Vec<f64>,
+    opd: Vec<f32>,
+    config: Arc<Config>,
+}
+impl Default for MockOpticalModel {
+    fn default() -> Self {
+        Self::new(DETECTOR_SIZE, 4.)
+    }
+}
+impl MockOpticalModel {
+    /// Create a mock model with a `n_px`×`n_px` detector and a Gaussian PSF of standard deviation `sigma` pixels
+    pub fn new(n_px: usize, sigma: f64) -> Self {
+        Self {
+            n_px,
+            sigma,
+            wander: sigma,
+            period: 20,
+            step: 0,
+            frame: vec![0f32; n_px * n_px],
+            pssn: vec![],
+            opd: vec![0f32; OPD_SIZE * OPD_SIZE],
+            config: Config::new(10. * sigma as f32, 2. * sigma as f32, 500.),
+        }
+    }
+    /// Set the radius in pixels and the period in frames of the PSF wander
+    pub fn wander(self, radius: f64, period: usize) -> Self {
+        Self {
+            wander: radius,
+            period: period.max(1),
+            ..self
+        }
+    }
+    /// Set the PSF rendering configuration
+    pub fn set_config(&mut self, config: Arc<Config>) {
+        self.config = config;
+    }
+    /// Returns the PSF center offset in pixels at the current step
+    fn offset(&self) -> (f64, f64) {
+        let (s, c) = (2. * PI * self.step as f64 / self.period as f64).sin_cos();
+        (self.wander * c, self.wander * s)
+    }
+}
+impl OpticalModel for MockOpticalModel {
+    fn ray_trace(&mut self) -> &mut Self {
+        let (dx, dy) = self.offset();
+        let o = 0.5 * (self.n_px - 1) as f64;
+        let s2 = 2. * self.sigma * self.sigma;
+        let n_px = self.n_px;
+        self.frame.iter_mut().enumerate().for_each(|(k, f)| {
+            let x = (k % n_px) as f64 - o - dx;
+            let y = (k / n_px) as f64 - o - dy;
+            *f += (-(x * x + y * y) / s2).exp() as f32;
+        });
+        self.pssn.push((-(dx * dx + dy * dy) / (2. * s2)).exp());
+        self.opd.iter_mut().enumerate().for_each(|(k, opd)| {
+            let x = (k % OPD_SIZE) as f64 / OPD_SIZE as f64 - 0.5;
+            let y = (k / OPD_SIZE) as f64 / OPD_SIZE as f64 - 0.5;
+            *opd = (1e-7 * (dx * x + dy * y)) as f32;
+        });
+        self.step += 1;
+        self
+    }
+    type Error = PSFError;
+    fn read_detector(&mut self) -> Result<PSF, Self::Error> {
+        let frame = std::mem::replace(&mut self.frame, vec![0f32; self.n_px * self.n_px]);
+        if frame.iter().all(|&x| x == 0.) {
+            return Err(PSFError::EmptyFrame);
+        }
+        Ok(PSF::new(&self.config, frame))
+    }
+    fn compute_pssn(&mut self) -> f64 {
+        if self.pssn.is_empty() {
+            1.
+        } else {
+            self.pssn.iter().sum::<f64>() / self.pssn.len() as f64
+        }
+    }
+    fn get_opd(&self) -> &[f32] {
+        self.opd.as_slice()
+    }
+    fn get_config(&self) -> Arc<Config> {
+        self.config.clone()
+    }
+}
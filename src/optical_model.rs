@@ -1,17 +1,38 @@
-use std::sync::Arc;
+use std::{
+    path::{Path as FilePath, PathBuf},
+    sync::Arc,
+};
 
 use crseo::{
     Atmosphere, Builder, CrseoError, FromBuilder, Gmt, Imaging, PSSn, PSSnEstimates, Source,
     imaging::Detector,
-    pssn::{PSSnBuilder, TelescopeError},
+    pssn::{AtmosphereTelescopeError, PSSnBuilder, TelescopeError},
 };
 use gmt_dos_clients_domeseeing::{DomeSeeing, DomeSeeingError};
-use object_store::{ObjectStore, path::Path};
+use image::{ImageError, Rgb, RgbImage};
+use object_store::{ObjectStore, local::LocalFileSystem, path::Path};
+use rand::{SeedableRng, rngs::StdRng};
 use skyangle::Conversion;
 
-use crate::{Config, DETECTOR_SIZE, PSF, PSFs, optical_model::windloads::WindLoadsError};
+use crate::{
+    Config, DETECTOR_SIZE, DomeSeeingMetadata, LongExposure, ModelMetadata, N_ZERNIKE, OpdStats,
+    Overlay, PSF, PSFs, PSSnErrorModel, RunConfig,
+    optical_model::{
+        jitter::{JitterError, tilt_opd},
+        noise::add_detector_noise,
+        windloads::{N_MIRROR_RBM, WindLoadsError},
+    },
+    psfs::opd_to_rgb,
+};
 
+mod frames;
+mod jitter;
+mod noise;
+mod retry;
 mod windloads;
+pub use frames::Frames;
+pub use jitter::Jitter;
+pub use retry::RetryPolicy;
 pub use windloads::WindLoads;
 
 #[derive(Debug, thiserror::Error)]
@@ -20,19 +41,117 @@ pub enum GmtOpticalModelError {
     Crseo(#[from] CrseoError),
     #[error("failed to build dome seeing")]
     DomeSeeing(#[from] DomeSeeingError),
+    #[error("failed to open the local CFD directory {1:?}")]
+    LocalCfdPath(#[source] object_store::Error, PathBuf),
     #[error("failed to build wind loads")]
     WindLoads(#[from] WindLoadsError),
+    #[error("failed to build pointing jitter")]
+    Jitter(#[from] JitterError),
+    #[error("unknown photometric band {0:?}, expected one of {:?}", BANDS)]
+    UnknownBand(String),
+    #[error("invalid atmosphere Fried parameter r0={0}m")]
+    FriedParameter(f64),
+    #[error("invalid atmosphere outer scale L0={0}m")]
+    OuterScale(f64),
+    #[error("at least one dither offset is required")]
+    NoDither,
+    #[error("invalid {0}x{0} detector with an oversampling factor of {1}")]
+    Detector(usize, usize),
+    #[error("the {2}x{3} detector window at ({0},{1}) exceeds the {4}x{4} detector")]
+    DetectorWindow(usize, usize, usize, usize, usize),
+    #[error("cannot seek the dome seeing OPDs back to sample #{0} from sample #{1}")]
+    Seek(usize, usize),
+    #[error("expected a static OPD map of {1} samples, found {0}")]
+    StaticOpd(usize, usize),
+    #[error("the detector frame is empty, no light reached the detector since the last read")]
+    EmptyFrame,
+    #[error("at least one field point is required")]
+    FieldPoints,
+    #[error("failed to save the pupil to png file {1:?}")]
+    SavePupil(#[source] ImageError, PathBuf),
+    #[error(
+        "the CFD case {case} (zenith {zenith}°, azimuth {azimuth}°, {enclosure} enclosure, {wind_speed}m/s) is not available for year {year}"
+    )]
+    InvalidCfdCase {
+        zenith: u32,
+        azimuth: u32,
+        enclosure: String,
+        wind_speed: u32,
+        year: u32,
+        case: String,
+    },
+}
+impl GmtOpticalModelError {
+    /// Creates the error of a CFD case that does not exist in the CFD dataset of the given year
+    pub fn invalid_cfd_case(run_config: &RunConfig, year: u32) -> Self {
+        Self::InvalidCfdCase {
+            zenith: run_config.zenith_angle.as_u32(),
+            azimuth: run_config.azimuth_angle.as_u32(),
+            enclosure: run_config.enclosure().to_string(),
+            wind_speed: run_config.wind_speed.as_u32(),
+            year,
+            case: run_config.colloquial(),
+        }
+    }
 }
 type Result<T> = std::result::Result<T, GmtOpticalModelError>;
 
+/// Number of consecutive frames the cumulative PSSn must be stable for to be converged
+const N_CONVERGED: usize = 5;
+/// Sampling rate of the CFD dome seeing OPDs and default frame rate in Hz
+const CFD_FRAME_RATE_HZ: f64 = 5.;
+
+/// Photometric bands supported by CRSEO
+pub const BANDS: [&str; 9] = ["V", "Vs", "R", "R+I", "I", "J", "H", "K", "Ks"];
+
+/// Outcome of [GmtOpticalModel::run_until_converged]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Convergence {
+    /// The cumulative PSSn has converged
+    Converged,
+    /// The maximum number of frames has been ray traced before the PSSn converged
+    MaxFrames,
+    /// The wind loads or the dome seeing time series are exhausted before the PSSn converged
+    OutOfData,
+}
+
+/// GMT optical model
+///
+/// The PSF rendering configuration is shared as an `Arc<Config>` with the PSF frames
+/// read out from the detector; the model is `Send` and can be built and moved across
+/// the await points of a tokio task, as in the PSSn batch tool.
 pub struct GmtOpticalModel {
     gmt: Gmt,
     src: Source,
     imgr: Imaging,
-    pssn: PSSn<TelescopeError>,
+    detector_shape: (usize, usize),
+    detector_origin: (usize, usize),
+    full_well: Option<f64>,
+    pssn: PSSnEstimator,
     domeseeing: Option<DomeSeeing>,
+    domeseeing_opd: Option<Vec<f64>>,
+    domeseeing_sample: usize,
+    domeseeing_path: Option<String>,
+    static_opd: Option<Vec<f64>>,
+    reference: Option<PSF>,
     windloads: Option<WindLoads>,
+    rbms: Option<Box<[f64]>>,
+    windloads_enabled: bool,
+    domeseeing_enabled: bool,
+    jitter: Option<Jitter>,
+    field: Option<FieldPoints>,
+    rng: StdRng,
+    seed: Option<u64>,
     config: Arc<Config>,
+    builder: GmtOpticalModelBuilder,
+}
+
+/// Off-axis sources and their detectors
+struct FieldPoints {
+    /// (zenith,azimuth) angles in arcseconds and degrees
+    points: Vec<(f64, f64)>,
+    src: Source,
+    imgr: Imaging,
 }
 #[cfg(feature = "verbose")]
 macro_rules! debug_println {
@@ -46,61 +165,217 @@ macro_rules! debug_println {
     ($($arg:tt)*) => {{}};
 }
 
+enum PSSnEstimator {
+    Telescope(PSSn<TelescopeError>),
+    AtmosphereTelescope(PSSn<AtmosphereTelescopeError>),
+}
+impl PSSnEstimator {
+    fn through(&mut self, src: &mut Source) {
+        match self {
+            Self::Telescope(pssn) => {
+                src.through(pssn);
+            }
+            Self::AtmosphereTelescope(pssn) => {
+                src.through(pssn);
+            }
+        }
+    }
+    fn estimates(&mut self) -> Vec<f64> {
+        match self {
+            Self::Telescope(pssn) => pssn.estimates(),
+            Self::AtmosphereTelescope(pssn) => pssn.estimates(),
+        }
+    }
+    fn reset(&mut self) {
+        match self {
+            Self::Telescope(pssn) => {
+                pssn.reset();
+            }
+            Self::AtmosphereTelescope(pssn) => {
+                pssn.reset();
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GmtOpticalModelBuilder {
     photometry: String,
+    pssn_photometry: Option<String>,
+    pssn_error_model: PSSnErrorModel,
+    atmosphere: bool,
+    detector_size: usize,
+    osf: usize,
+    r0: Option<f64>,
+    l0: Option<f64>,
 }
 impl Default for GmtOpticalModelBuilder {
     fn default() -> Self {
         Self {
             photometry: String::from("Vs"),
+            pssn_photometry: None,
+            pssn_error_model: Default::default(),
+            atmosphere: true,
+            detector_size: DETECTOR_SIZE,
+            osf: 4,
+            r0: None,
+            l0: None,
         }
     }
 }
 impl GmtOpticalModelBuilder {
     pub fn v_band(self) -> Self {
-        Default::default()
+        Self {
+            photometry: String::from("Vs"),
+            ..self
+        }
     }
     pub fn h_band(self) -> Self {
         Self {
             photometry: String::from("H"),
+            ..self
         }
     }
-    pub fn build(self) -> Result<GmtOpticalModel> {
-        // Setup GMT optics and imaging
-        let gmt = Gmt::builder().build()?;
-        let src = Source::builder().band(self.photometry.as_str());
-        let pssn = PSSnBuilder::<TelescopeError>::default()
-            .source(src.clone())
-            .build()?;
-
-        let src = src.build()?;
-
-        // Get wavelength in nanometers for PSSN display
-        // let wavelength_nm = src.wavelength() * 1e9; // Convert meters to nanometers
-
-        let imgr = Imaging::builder()
-            .detector(
-                Detector::default()
-                    .n_px_imagelet(DETECTOR_SIZE)
-                    .n_px_framelet(DETECTOR_SIZE)
-                    .osf(4),
-            )
-            .build()?;
-
+    /// Sets the photometric band of the imaging source, one of [BANDS]
+    ///
+    /// The PSSn is evaluated in the same band, unless set otherwise with
+    /// [GmtOpticalModelBuilder::pssn_band]
+    pub fn band(self, band: &str) -> Self {
+        Self {
+            photometry: band.to_string(),
+            ..self
+        }
+    }
+    /// Selects the PSSn estimator error model (default: [PSSnErrorModel::Telescope])
+    pub fn pssn_error_model(self, pssn_error_model: PSSnErrorModel) -> Self {
+        Self {
+            pssn_error_model,
+            ..self
+        }
+    }
+    /// Sets the photometric band the PSSn is evaluated in
+    ///
+    /// By default, the PSSn is evaluated in the same band than the images.
+    /// The wavefront of the imaging source is used to compute the PSSn at the
+    /// wavelength of the PSSn band: this is only valid as long as the wavefront
+    /// errors are achromatic, as for dome seeing and wind loads, and the
+    /// atmospheric PSSn error model does not account for the chromaticity of
+    /// the atmospheric seeing.
+    pub fn pssn_band(self, band: &str) -> Self {
+        Self {
+            pssn_photometry: Some(band.to_string()),
+            ..self
+        }
+    }
+    /// Removes the atmospheric seeing contribution
+    ///
+    /// The PSSn is then evaluated with the [PSSnErrorModel::Telescope] error model
+    /// whatever the error model selected with [GmtOpticalModelBuilder::pssn_error_model]
+    /// and the seeing circle overlay is not drawn.
+    /// The PSFs only include the dome seeing, the wind loads and the pointing jitter.
+    pub fn without_atmosphere(self) -> Self {
+        Self {
+            atmosphere: false,
+            ..self
+        }
+    }
+    /// Sets the detector size to `n_px`×`n_px` pixels and the Nyquist oversampling factor to `osf`
+    ///
+    /// The default detector is [DETECTOR_SIZE]×[DETECTOR_SIZE] pixels with an oversampling factor of 4
+    pub fn detector(self, n_px: usize, osf: usize) -> Self {
+        Self {
+            detector_size: n_px,
+            osf,
+            ..self
+        }
+    }
+    /// Sets the Fried parameter of the atmosphere in meters at 500nm
+    ///
+    /// The default is the CRSEO atmosphere Fried parameter.
+    /// The seeing circle overlay is sized accordingly.
+    pub fn r0(self, r0: f64) -> Self {
+        Self {
+            r0: Some(r0),
+            ..self
+        }
+    }
+    /// Sets the outer scale of the atmosphere in meters
+    ///
+    /// The default is the CRSEO atmosphere outer scale.
+    pub fn l0(self, l0: f64) -> Self {
+        Self {
+            l0: Some(l0),
+            ..self
+        }
+    }
+    /// Sets the atmospheric seeing FWHM in arcseconds at 500nm
+    ///
+    /// The Fried parameter is derived from the seeing as `r0=0.98λ/FWHM`,
+    /// see [GmtOpticalModelBuilder::r0]
+    pub fn seeing(self, fwhm_arcsec: f64) -> Self {
+        self.r0(0.98 * 500e-9 / fwhm_arcsec.from_arcsec())
+    }
+    /// Photometric band the PSSn is evaluated in
+    fn pssn_band(&self) -> String {
+        self.pssn_photometry
+            .clone()
+            .unwrap_or_else(|| self.photometry.clone())
+    }
+    /// PSSn estimator error model, accounting for the atmosphere being removed
+    fn effective_pssn_error_model(&self) -> PSSnErrorModel {
+        if self.atmosphere {
+            self.pssn_error_model
+        } else {
+            PSSnErrorModel::Telescope
+        }
+    }
+    /// Builds the PSSn estimator and returns it with the PSSn wavelength in nanometers
+    fn build_pssn(&self) -> Result<(PSSnEstimator, f64)> {
+        let pssn_src = Source::builder().band(self.pssn_band().as_str());
+        let pssn = match self.effective_pssn_error_model() {
+            PSSnErrorModel::Telescope => PSSnEstimator::Telescope(
+                PSSnBuilder::<TelescopeError>::default()
+                    .source(pssn_src.clone())
+                    .build()?,
+            ),
+            PSSnErrorModel::AtmosphereTelescope => PSSnEstimator::AtmosphereTelescope(
+                PSSnBuilder::<AtmosphereTelescopeError>::default()
+                    .source(pssn_src.clone())
+                    .build()?,
+            ),
+        };
+        // PSSn wavelength in nanometers for PSSN display
+        let pssn_wavelength_nm = pssn_src.build()?.wavelength() * 1e9;
+        Ok((pssn, pssn_wavelength_nm))
+    }
+    /// Builds the PSF rendering configuration for the imaging source
+    fn build_config(
+        &self,
+        src: &Source,
+        imgr: &Imaging,
+        pssn_wavelength_nm: f64,
+    ) -> Result<Arc<Config>> {
         #[cfg(feature = "verbose")]
         let gmt_diff_lim = (1.22 * src.wavelength() / 25.5).to_mas();
         let gmt_segment_diff_lim = (1.22 * src.wavelength() / 8.365).to_mas() as f32;
         debug_println!("GMT diffraction limited FWHM: {:.0}mas", gmt_diff_lim);
         // pixel scale
-        let px = imgr.pixel_scale(&src).to_mas();
+        let px = imgr.pixel_scale(src).to_mas();
         debug_println!(
             "Detector: pixel scale: {:.0}mas, FOV: {:.2}arcsec",
             px,
-            imgr.field_of_view(&src).to_mas()
+            imgr.field_of_view(src).to_mas()
         );
 
-        let atm = Atmosphere::builder().build()?;
+        let atm = match self.r0 {
+            Some(r0) => Atmosphere::builder().r0_at_zenith(r0),
+            None => Atmosphere::builder(),
+        };
+        let atm = match self.l0 {
+            Some(l0) => atm.oscale(l0),
+            None => atm,
+        }
+        .build()?;
         let seeing = (0.98 * src.wavelength() / atm.r0()).to_mas() as f32;
         debug_println!("Atmosphere seeing: {:.0}mas", seeing);
 
@@ -113,80 +388,146 @@ impl GmtOpticalModelBuilder {
         let config = Config::new(
             seeing_radius_pixels,
             segment_diff_lim_radius_pixels,
-            src.wavelength() * 1e9,
+            pssn_wavelength_nm,
         );
-        Ok(GmtOpticalModel {
-            gmt,
-            src,
-            imgr,
-            pssn,
-            domeseeing: None,
-            windloads: None,
-            config,
-        })
+        let config = if self.atmosphere {
+            config
+        } else {
+            config.overlays(vec![Overlay::new(
+                "segment diff. limit radius",
+                segment_diff_lim_radius_pixels,
+            )])
+        }
+        .model(ModelMetadata {
+            band: self.photometry.clone(),
+            pssn_band: self.pssn_band(),
+            detector_size: self.detector_size,
+            osf: self.osf,
+            pixel_scale_mas: px as f64,
+            atmosphere_r0: atm.r0(),
+            atmosphere_l0: self.l0,
+            frame_rate_hz: CFD_FRAME_RATE_HZ,
+            pssn_error_model: self.effective_pssn_error_model(),
+            atmosphere: self.atmosphere,
+            ..Default::default()
+        });
+        Ok(config)
     }
-}
-
-impl GmtOpticalModel {
-    pub fn new() -> Result<Self> {
-        // Setup GMT optics and imaging
-        let gmt = Gmt::builder().build()?;
-        let src = Source::builder().band("Vs");
-        let pssn = PSSnBuilder::<TelescopeError>::default()
-            .source(src.clone())
-            .build()?;
-
-        let src = src.build()?;
-
-        // Get wavelength in nanometers for PSSN display
-        // let wavelength_nm = src.wavelength() * 1e9; // Convert meters to nanometers
-
-        let imgr = Imaging::builder()
+    /// Builds the imaging detector of `n_sensor` sources
+    fn build_imaging(&self, n_sensor: usize) -> Result<Imaging> {
+        Ok(Imaging::builder()
             .detector(
                 Detector::default()
-                    .n_px_imagelet(DETECTOR_SIZE)
-                    .n_px_framelet(DETECTOR_SIZE)
-                    .osf(4),
+                    .n_px_imagelet(self.detector_size)
+                    .n_px_framelet(self.detector_size)
+                    .osf(self.osf),
             )
+            .n_sensor(n_sensor)
+            .build()?)
+    }
+    /// Builds the sources of the field points and their detectors
+    fn build_field_points(&self, points: &[(f64, f64)]) -> Result<FieldPoints> {
+        if points.is_empty() {
+            return Err(GmtOpticalModelError::FieldPoints);
+        }
+        let (zenith, azimuth): (Vec<f32>, Vec<f32>) = points
+            .iter()
+            .map(|&(z, a)| (z.from_arcsec() as f32, a.to_radians() as f32))
+            .unzip();
+        let src = Source::builder()
+            .size(points.len())
+            .band(self.photometry.as_str())
+            .zenith_azimuth(zenith, azimuth)
             .build()?;
+        Ok(FieldPoints {
+            points: points.to_vec(),
+            src,
+            imgr: self.build_imaging(points.len())?,
+        })
+    }
+    pub fn build(self) -> Result<GmtOpticalModel> {
+        if !BANDS.contains(&self.photometry.as_str()) {
+            return Err(GmtOpticalModelError::UnknownBand(self.photometry));
+        }
+        if let Some(r0) = self.r0.filter(|r0| !(r0.is_finite() && *r0 > 0.)) {
+            return Err(GmtOpticalModelError::FriedParameter(r0));
+        }
+        if let Some(l0) = self.l0.filter(|l0| !(l0.is_finite() && *l0 > 0.)) {
+            return Err(GmtOpticalModelError::OuterScale(l0));
+        }
+        if self.detector_size == 0 || self.osf == 0 {
+            return Err(GmtOpticalModelError::Detector(self.detector_size, self.osf));
+        }
+        // Setup GMT optics and imaging
+        let gmt = Gmt::builder().build()?;
+        let src = Source::builder().band(self.photometry.as_str()).build()?;
+        let (pssn, pssn_wavelength_nm) = self.build_pssn()?;
 
-        #[cfg(feature = "verbose")]
-        let gmt_diff_lim = (1.22 * src.wavelength() / 25.5).to_mas();
-        let gmt_segment_diff_lim = (1.22 * src.wavelength() / 8.365).to_mas() as f32;
-        debug_println!("GMT diffraction limited FWHM: {:.0}mas", gmt_diff_lim);
-        // pixel scale
-        let px = imgr.pixel_scale(&src).to_mas();
-        debug_println!(
-            "Detector: pixel scale: {:.0}mas, FOV: {:.2}arcsec",
-            px,
-            imgr.field_of_view(&src).to_mas()
-        );
-
-        let atm = Atmosphere::builder().build()?;
-        let seeing = (0.98 * src.wavelength() / atm.r0()).to_mas() as f32;
-        debug_println!("Atmosphere seeing: {:.0}mas", seeing);
+        let imgr = self.build_imaging(1)?;
 
-        // Calculate seeing radius in pixels (diameter = 2 * radius, so radius = seeing / 2 / px)
-        let seeing_radius_pixels = (seeing / 2.0) / px;
-        // Calculate GMT segment diff lim radius in pixels
-        let segment_diff_lim_radius_pixels = (gmt_segment_diff_lim / 2.0) / px;
-        // debug_println!("Seeing radius in pixels: {:.1}px", seeing_radius_pixels);
-        // debug_println!("GMT segment diff lim radius in pixels: {:.1}px", segment_diff_lim_radius_pixels);
-        let config = Config::new(
-            seeing_radius_pixels,
-            segment_diff_lim_radius_pixels,
-            src.wavelength() * 1e9,
-        );
-        Ok(Self {
+        let config = self.build_config(&src, &imgr, pssn_wavelength_nm)?;
+        Ok(GmtOpticalModel {
             gmt,
             src,
             imgr,
+            detector_shape: (self.detector_size, self.detector_size),
+            detector_origin: (0, 0),
+            full_well: None,
             pssn,
             domeseeing: None,
+            domeseeing_opd: None,
+            domeseeing_sample: 0,
+            domeseeing_path: None,
+            static_opd: None,
+            reference: None,
             windloads: None,
+            rbms: None,
+            windloads_enabled: true,
+            domeseeing_enabled: true,
+            jitter: None,
+            field: None,
+            rng: StdRng::from_os_rng(),
+            seed: None,
             config,
+            builder: self,
         })
     }
+}
+
+impl GmtOpticalModel {
+    pub fn new() -> Result<Self> {
+        Self::with_detector(DETECTOR_SIZE, 4)
+    }
+    /// Creates a new GMT optical model with a `n_px`×`n_px` detector
+    /// and a Nyquist oversampling factor of `osf`
+    ///
+    /// An error is returned if either `n_px` or `osf` is zero
+    pub fn with_detector(n_px: usize, osf: usize) -> Result<Self> {
+        Self::builder().detector(n_px, osf).build()
+    }
+    /// Creates a new GMT optical model imaging in the given photometric band
+    ///
+    /// The diffraction limit and seeing overlays and the PSSn are computed in the same band.
+    /// An error is returned if the band is not one of [BANDS]
+    pub fn with_band(band: &str) -> Result<Self> {
+        Self::builder().band(band).build()
+    }
+    /// Creates a new GMT optical model with the given atmospheric seeing FWHM in arcseconds
+    ///
+    /// See [GmtOpticalModelBuilder::seeing]
+    pub fn with_seeing(fwhm_arcsec: f64) -> Result<Self> {
+        Self::builder().seeing(fwhm_arcsec).build()
+    }
+    /// Creates a new GMT optical model with the given PSSn estimator error model
+    pub fn new_with_pssn(pssn_error_model: PSSnErrorModel) -> Result<Self> {
+        Self::builder().pssn_error_model(pssn_error_model).build()
+    }
+    /// Creates a new GMT optical model without the atmospheric seeing contribution
+    ///
+    /// See [GmtOpticalModelBuilder::without_atmosphere]
+    pub fn without_atmosphere() -> Result<Self> {
+        Self::builder().without_atmosphere().build()
+    }
     pub fn builder() -> GmtOpticalModelBuilder {
         Default::default()
     }
@@ -196,15 +537,80 @@ impl GmtOpticalModel {
     pub fn set_config(&mut self, config: Arc<Config>) {
         self.config = config;
     }
+    /// Changes the photometric band of the imaging source
+    ///
+    /// Only the source is rebuilt, together with the PSSn estimator if the PSSn
+    /// is evaluated in the same band than the images.
+    /// The PSF rendering configuration is updated for the new pixel scale and
+    /// overlay radii, keeping the CFD case, turbulence effects and display settings; custom
+    /// overlays are replaced by the default ones.
+    /// The detector is reset.
+    pub fn set_band(&mut self, band: &str) -> Result<()> {
+        if !BANDS.contains(&band) {
+            return Err(GmtOpticalModelError::UnknownBand(band.to_string()));
+        }
+        let builder = GmtOpticalModelBuilder {
+            photometry: band.to_string(),
+            ..self.builder.clone()
+        };
+        let src = Source::builder().band(band).build()?;
+        let pssn_wavelength_nm = if builder.pssn_photometry.is_none() {
+            let (pssn, pssn_wavelength_nm) = builder.build_pssn()?;
+            self.pssn = pssn;
+            pssn_wavelength_nm
+        } else {
+            self.config.wavelength_nm
+        };
+        let config = builder.build_config(&src, &self.imgr, pssn_wavelength_nm)?;
+        self.config = Arc::new(Config {
+            cfd_case: self.config.cfd_case.clone(),
+            turbulence_effects: self.config.turbulence_effects.clone(),
+            legend: self.config.legend,
+            overlay_frames: self.config.overlay_frames,
+            wind_azimuth_deg: self.config.wind_azimuth_deg,
+            colorbar: self.config.colorbar,
+            colormap: self.config.colormap,
+            normalization: self.config.normalization,
+            detector_noise: self.config.detector_noise,
+            ..(*config).clone()
+        });
+        if let Some(field) = self.field.take() {
+            self.field = Some(builder.build_field_points(&field.points)?);
+        }
+        self.src = src;
+        self.imgr.reset();
+        self.reference = None;
+        self.builder = builder;
+        self.update_metadata();
+        Ok(())
+    }
     pub fn gmt(&mut self) -> &mut Gmt {
         &mut self.gmt
     }
-    pub async fn domeseeing(
+    /// Loads the dome seeing OPDs of a CFD case from a local directory
+    ///
+    /// This is [GmtOpticalModel::domeseeing_from_store] with a [LocalFileSystem] store
+    /// rooted at `cfd_path`
+    pub async fn domeseeing(self, cfd_path: impl AsRef<FilePath>) -> Result<Self> {
+        let cfd_path = cfd_path.as_ref();
+        let store = LocalFileSystem::new_with_prefix(cfd_path)
+            .map_err(|e| GmtOpticalModelError::LocalCfdPath(e, cfd_path.to_path_buf()))?;
+        let mut this = self.domeseeing_from_store(store, Path::default()).await?;
+        this.domeseeing_path = Some(cfd_path.display().to_string());
+        this.update_metadata();
+        Ok(this)
+    }
+    /// Loads the dome seeing OPDs of a CFD case from an object store
+    pub async fn domeseeing_from_store(
         mut self,
         store: impl ObjectStore,
         cfd_path: impl Into<Path>,
     ) -> Result<Self> {
+        let cfd_path: Path = cfd_path.into();
+        self.domeseeing_path = Some(cfd_path.to_string());
         self.domeseeing = Some(DomeSeeing::builder(cfd_path).store(store).build().await?);
+        self.domeseeing_sample = 0;
+        self.update_metadata();
         Ok(self)
     }
     pub async fn windloads(
@@ -213,95 +619,858 @@ impl GmtOpticalModel {
         rbms_path: impl Into<object_store::path::Path>,
     ) -> Result<Self> {
         self.windloads = Some(WindLoads::new(storage, rbms_path).await?);
+        self.update_metadata();
         Ok(self)
     }
-    pub fn ray_trace(&mut self) -> &mut Self {
-        // updating M1 & M2 rigid body motions
-        self.windloads.as_mut().map(|windloads| {
-            windloads.next().map(|rbms| {
-                let (m1_rbms, m2_rbms) = rbms.split_at(42);
-                self.gmt.update42(Some(m1_rbms), Some(m2_rbms), None, None);
-            })
+    /// Loads the wind loads RBMs from an arbitrary parquet file
+    ///
+    /// Contrary to [GmtOpticalModel::windloads], an error is returned
+    /// up front if the file does not exist in the object store
+    pub async fn windloads_from(
+        mut self,
+        storage: impl ObjectStore,
+        rbms_path: impl Into<object_store::path::Path>,
+    ) -> Result<Self> {
+        self.windloads = Some(WindLoads::new_checked(storage, rbms_path).await?);
+        self.update_metadata();
+        Ok(self)
+    }
+    /// Loads the M1 & M2 rigid body motions sampled at `input_hz` and downsampled to `output_hz`
+    ///
+    /// The first `skip_seconds` of the time series are skipped, see [WindLoads::with_sampling];
+    /// the frame rate of the model metadata is set to `output_hz`.
+    pub async fn windloads_with_sampling(
+        mut self,
+        storage: impl ObjectStore,
+        rbms_path: impl Into<object_store::path::Path>,
+        input_hz: usize,
+        output_hz: usize,
+        skip_seconds: f64,
+    ) -> Result<Self> {
+        self.windloads = Some(
+            WindLoads::with_sampling(storage, rbms_path, input_hz, output_hz, skip_seconds).await?,
+        );
+        self.update_metadata();
+        Ok(self)
+    }
+    /// Loads the dome seeing OPDs, retrying transient object store failures
+    pub async fn domeseeing_with_retry(
+        mut self,
+        store: impl ObjectStore + Clone,
+        cfd_path: impl Into<Path>,
+        policy: &RetryPolicy,
+    ) -> Result<Self> {
+        let cfd_path: Path = cfd_path.into();
+        self.domeseeing = Some(
+            policy
+                .retry(|| {
+                    DomeSeeing::builder(cfd_path.clone())
+                        .store(store.clone())
+                        .build()
+                })
+                .await?,
+        );
+        self.domeseeing_sample = 0;
+        self.domeseeing_path = Some(cfd_path.to_string());
+        self.update_metadata();
+        Ok(self)
+    }
+    /// Loads the M1 & M2 rigid body motions, retrying transient object store failures
+    pub async fn windloads_with_retry(
+        mut self,
+        storage: impl ObjectStore + Clone,
+        rbms_path: impl Into<object_store::path::Path>,
+        policy: &RetryPolicy,
+    ) -> Result<Self> {
+        self.windloads = Some(WindLoads::new_with_retry(storage, rbms_path, policy).await?);
+        self.update_metadata();
+        Ok(self)
+    }
+    /// Sets the M1 & M2 rigid body motions, e.g. loaded with [WindLoads::with_sampling]
+    pub fn with_windloads(mut self, windloads: WindLoads) -> Self {
+        self.windloads = Some(windloads);
+        self.update_metadata();
+        self
+    }
+    /// Returns a mutable reference to the wind loads, if loaded
+    ///
+    /// It gives access to the [WindLoads] time series controls, like [WindLoads::seek];
+    /// use [GmtOpticalModel::set_windloads_gain] for the gain to be recorded in the model metadata
+    pub fn windloads_mut(&mut self) -> Option<&mut WindLoads> {
+        self.windloads.as_mut()
+    }
+    /// Returns the number of wind loads samples left to ray trace
+    ///
+    /// Returns `None` if the wind loads have not been loaded
+    pub fn windloads_len(&self) -> Option<usize> {
+        self.windloads.as_ref().map(|windloads| windloads.len())
+    }
+    /// Scales the wind loads rigid body motions by `gain`
+    ///
+    /// This has no effect if the wind loads have not been loaded yet
+    pub fn set_windloads_gain(&mut self, gain: f64) -> &mut Self {
+        if let Some(windloads) = self.windloads.as_mut() {
+            windloads.set_gain(gain);
+        }
+        self.update_metadata();
+        self
+    }
+    /// Updates the model metadata of the PSF rendering configuration
+    ///
+    /// The seed, the dome seeing and the wind loads settings are recorded and
+    /// the frame rate is the wind loads output sampling rate, if loaded,
+    /// or the CFD dome seeing sampling rate
+    fn update_metadata(&mut self) {
+        let Some(mut model) = self.config.model.clone() else {
+            return;
+        };
+        model.seed = self.seed;
+        model.domeseeing = self.domeseeing_path.clone().map(|path| DomeSeeingMetadata {
+            path,
+            enabled: self.domeseeing_enabled,
         });
-
+        model.windloads = self
+            .windloads
+            .as_ref()
+            .map(|windloads| windloads.metadata(self.windloads_enabled));
+        model.frame_rate_hz = model
+            .windloads
+            .as_ref()
+            .map_or(CFD_FRAME_RATE_HZ, |windloads| windloads.output_hz);
+        self.config = self.config.clone().model(model);
+    }
+    /// Enables or disables the wind loads
+    ///
+    /// The wind loads time series still moves forward with every ray trace while disabled,
+    /// with M1 & M2 kept aligned, so the same frame can be rendered with and without the wind loads
+    pub fn enable_windloads(&mut self, enable: bool) -> &mut Self {
+        self.windloads_enabled = enable;
+        self.update_metadata();
+        self
+    }
+    /// Enables or disables the dome seeing
+    ///
+    /// The dome seeing time series still moves forward with every ray trace while disabled,
+    /// without adding the OPD maps to the wavefront, so the same frame can be rendered
+    /// with and without the dome seeing
+    pub fn enable_domeseeing(&mut self, enable: bool) -> &mut Self {
+        self.domeseeing_enabled = enable;
+        self.update_metadata();
+        self
+    }
+    /// Updates M1 & M2 with the wind loads rigid body motions
+    ///
+    /// The rigid body motions are zeroed if the wind loads are disabled
+    fn update_windloads(&mut self, mut rbms: Box<[f64]>) {
+        if !self.windloads_enabled {
+            rbms.fill(0.);
+        }
+        let (m1_rbms, m2_rbms) = rbms.split_at(N_MIRROR_RBM);
+        self.gmt.update42(Some(m1_rbms), Some(m2_rbms), None, None);
+        self.rbms = Some(rbms);
+    }
+    fn add_domeseeing_to_wavefront(&mut self, opd: Vec<f64>) {
+        self.domeseeing_sample += 1;
+        if self.domeseeing_enabled {
+            self.src.add(opd.as_slice());
+            self.domeseeing_opd = Some(opd);
+        } else {
+            self.domeseeing_opd = None;
+        }
+    }
+    /// Ray traces the source to the detector with the next samples of the time series
+    ///
+    /// M1 & M2 are updated with the wind loads rigid body motions `rbms` and
+    /// the dome seeing OPD map `opd` is added to the wavefront, if any
+    fn step(&mut self, rbms: Option<Box<[f64]>>, opd: Option<Vec<f64>>) -> &mut Self {
+        if let Some(rbms) = rbms {
+            self.update_windloads(rbms);
+        }
         self.src.through(&mut self.gmt).xpupil();
-
-        // adding dome seeing OPD map to the wavefront
-        self.domeseeing
-            .as_mut()
-            .map(|domeseeing| domeseeing.next().map(|opd| self.src.add(opd.as_slice())));
-
+        if let Some(opd) = opd {
+            self.add_domeseeing_to_wavefront(opd);
+        }
+        self.add_static_opd_to_wavefront();
+        self.add_jitter();
         self.src.through(&mut self.imgr);
+        self.trace_field_points();
         self
     }
-    pub fn ray_trace_all(&mut self) -> Option<&mut Self> {
-        // updating M1 & M2 rigid body motions
-        if let None = self
+    /// Ray traces the source to the detector, moving the wind loads and the dome seeing
+    /// time series forward
+    ///
+    /// Once a time series is exhausted, the source is ray traced without it
+    pub fn ray_trace(&mut self) -> &mut Self {
+        let rbms = self
             .windloads
             .as_mut()
-            .map(|windloads| {
-                windloads.next().map(|rbms| {
-                    let (m1_rbms, m2_rbms) = rbms.split_at(42);
-                    self.gmt.update42(Some(m1_rbms), Some(m2_rbms), None, None);
-                })
-            })
-            .flatten()
-        {
-            return None;
-        };
-
-        self.src.through(&mut self.gmt).xpupil();
-
-        // adding dome seeing OPD map to the wavefront
-        if let None = self
+            .and_then(|windloads| windloads.next());
+        let opd = self
             .domeseeing
             .as_mut()
-            .map(|domeseeing| domeseeing.next().map(|opd| self.src.add(opd.as_slice())))
-            .flatten()
-        {
-            return None;
+            .and_then(|domeseeing| domeseeing.next());
+        self.step(rbms, opd)
+    }
+    /// Ray traces the source to the detector, moving the wind loads and the dome seeing
+    /// time series forward
+    ///
+    /// Returns `None`, without ray tracing, as soon as the wind loads or the dome seeing
+    /// time series, if loaded, are exhausted
+    pub fn ray_trace_all(&mut self) -> Option<&mut Self> {
+        let rbms = match self.windloads.as_mut() {
+            Some(windloads) => Some(windloads.next()?),
+            None => None,
         };
-
-        self.src.through(&mut self.imgr);
-        Some(self)
+        let opd = match self.domeseeing.as_mut() {
+            Some(domeseeing) => Some(domeseeing.next()?),
+            None => None,
+        };
+        Some(self.step(rbms, opd))
     }
+    /// Returns an iterator of at most `n_frame` PSF frames
+    ///
+    /// The iterator mutably borrows the model, see [Frames]
+    pub fn frames(&mut self, n_frame: usize) -> Frames<'_> {
+        Frames::new(self, n_frame)
+    }
+    /// Same as [GmtOpticalModel::ray_trace] but loading the dome seeing OPD maps asynchronously
     pub async fn async_ray_trace(&mut self) -> &mut Self {
-        // updating M1 & M2 rigid body motions
-        self.windloads.as_mut().map(|windloads| {
-            windloads.next().map(|rbms| {
-                let (m1_rbms, m2_rbms) = rbms.split_at(42);
-                self.gmt.update42(Some(m1_rbms), Some(m2_rbms), None, None);
-            })
-        });
-
-        self.src.through(&mut self.gmt).xpupil();
-
-        // adding dome seeing OPD map to the wavefront
-        if let Some(domeseeing) = self.domeseeing.as_mut() {
-            domeseeing
-                .async_next()
-                .await
-                .map(|opd| self.src.add(opd.as_slice()));
+        let rbms = self
+            .windloads
+            .as_mut()
+            .and_then(|windloads| windloads.next());
+        let opd = match self.domeseeing.as_mut() {
+            Some(domeseeing) => domeseeing.async_next().await,
+            None => None,
         };
-
-        self.src.through(&mut self.imgr);
-        self
+        self.step(rbms, opd)
+    }
+    /// Estimates the Fried parameter in centimeters (at 500nm) equivalent to the current dome seeing OPD
+    ///
+    /// The phase structure function of the last dome seeing OPD map added to the
+    /// wavefront is computed along both axis of the pupil for separations of up to
+    /// 1/8th of the pupil size and matched to the Kolmogorov structure function
+    /// `6.88(r/r0)^(5/3)`.
+    /// Samples outside the aperture (zero or not finite) are ignored.
+    ///
+    /// Returns `None` if no dome seeing is attached to the model, if it is disabled
+    /// or before the 1st ray trace
+    pub fn effective_r0_cm(&self) -> Option<f64> {
+        let opd = self.domeseeing_opd.as_ref()?;
+        let n = self.src.pupil_sampling as usize;
+        let delta = self.src.pupil_size / (n - 1) as f64;
+        effective_r0(opd, n, delta, 500e-9).map(|r0| r0 * 1e2)
+    }
+    /// Applies a random pointing jitter to the source every frame
+    ///
+    /// The tip and tilt are drawn from a Gaussian distribution of RMS `rms_mas`
+    /// in milli-arcseconds and are added to the wavefront before the detector
+    /// so the PSF centroid is shifted accordingly
+    pub fn set_jitter(&mut self, rms_mas: f64, seed: u64) -> Result<&mut Self> {
+        self.jitter = Some(Jitter::new(rms_mas, seed)?);
+        Ok(self)
+    }
+    /// Returns the realized per-frame (tip,tilt) jitter offsets in milli-arcseconds
+    pub fn jitter_offsets(&self) -> Option<&[(f64, f64)]> {
+        self.jitter.as_ref().map(|jitter| jitter.offsets())
+    }
+    fn add_jitter(&mut self) {
+        if let Some(jitter) = self.jitter.as_mut() {
+            let n = self.src.pupil_sampling as usize;
+            let opd = jitter.opd(n, self.src.pupil_size);
+            self.src.add(opd.as_slice());
+        }
+    }
+    /// Adds a static OPD map (e.g. a mirror polishing error map) to the wavefront every frame
+    ///
+    /// The OPD is given in meters on the square grid of [pupil_sampling](Self::pupil_sampling)
+    /// by [pupil_sampling](Self::pupil_sampling) samples spanning the pupil,
+    /// in row-major order like the dome seeing OPD maps,
+    /// and it is added on top of the dome seeing OPD.
+    pub fn add_static_opd(&mut self, opd: &[f64]) -> Result<&mut Self> {
+        let n = self.pupil_sampling();
+        if opd.len() != n * n {
+            return Err(GmtOpticalModelError::StaticOpd(opd.len(), n * n));
+        }
+        self.static_opd = Some(opd.to_vec());
+        Ok(self)
+    }
+    /// Images the given field points together with the on-axis source
+    ///
+    /// The field points are (zenith,azimuth) angles in arcseconds and degrees, respectively.
+    /// A single multi-directional source is ray traced through the same state of the telescope,
+    /// of the dome seeing, of the static OPD and of the pointing jitter than the on-axis source
+    /// (the dome seeing OPD map is assumed to be the same across the field) on every ray trace,
+    /// the PSFs of the field points are read out with [GmtOpticalModel::read_detectors].
+    ///
+    /// An error is returned if `points` is empty
+    pub fn with_field_points(&mut self, points: &[(f64, f64)]) -> Result<&mut Self> {
+        self.field = Some(self.builder.build_field_points(points)?);
+        Ok(self)
+    }
+    /// Returns the (zenith,azimuth) field points, see [GmtOpticalModel::with_field_points]
+    pub fn field_points(&self) -> Option<&[(f64, f64)]> {
+        self.field.as_ref().map(|field| field.points.as_slice())
+    }
+    fn trace_field_points(&mut self) {
+        let Some(field) = self.field.as_mut() else {
+            return;
+        };
+        let n_point = field.points.len();
+        field.src.through(&mut self.gmt).xpupil();
+        if let Some(opd) = self.domeseeing_opd.as_ref() {
+            field.src.add(opd.repeat(n_point).as_slice());
+        }
+        if let Some(opd) = self.static_opd.as_ref() {
+            field.src.add(opd.repeat(n_point).as_slice());
+        }
+        if let Some(&(tip, tilt)) = self
+            .jitter
+            .as_ref()
+            .and_then(|jitter| jitter.offsets().last())
+        {
+            let n = field.src.pupil_sampling as usize;
+            let opd = tilt_opd(n, field.src.pupil_size, tip, tilt);
+            field.src.add(opd.repeat(n_point).as_slice());
+        }
+        field.src.through(&mut field.imgr);
+    }
+    /// Returns the number of samples across the pupil
+    pub fn pupil_sampling(&self) -> usize {
+        self.src.pupil_sampling as usize
+    }
+    fn add_static_opd_to_wavefront(&mut self) {
+        if let Some(opd) = self.static_opd.as_ref() {
+            self.src.add(opd.as_slice());
+        }
+    }
+    /// Returns the PSSn cumulated over `n` frames starting from sample #`start`
+    ///
+    /// The wind loads RBMs and the dome seeing OPDs are moved to sample #`start`,
+    /// the PSSn estimator is reset and `n` frames are ray traced.
+    /// The detector is reset afterwards.
+    ///
+    /// The dome seeing OPDs are streamed and can only be moved forward,
+    /// an error is returned, leaving the model unchanged, if sample #`start` has already
+    /// been ray traced.
+    pub fn pssn_over(&mut self, start: usize, n: usize) -> Result<f64> {
+        if self.domeseeing.is_some() && start < self.domeseeing_sample {
+            return Err(GmtOpticalModelError::Seek(start, self.domeseeing_sample));
+        }
+        if let Some(windloads) = self.windloads.as_mut() {
+            windloads.seek_sample(start);
+        }
+        if let Some(domeseeing) = self.domeseeing.as_mut() {
+            for _ in self.domeseeing_sample..start {
+                domeseeing.next();
+            }
+            self.domeseeing_sample = start;
+        }
+        self.pssn.reset();
+        for _ in 0..n {
+            self.ray_trace();
+            self.pssn.through(&mut self.src);
+        }
+        self.imgr.reset();
+        Ok(self.pssn.estimates()[0])
+    }
+    /// Ray traces frames until the cumulative PSSn has converged
+    ///
+    /// The PSSn is converged when its change from one frame to the next is less than `tol`
+    /// for 5 consecutive frames.
+    /// At least `min_frames` and at most `max_frames` frames are ray traced.
+    /// The frames are integrated on the detector, reading it out afterwards
+    /// gives the long exposure PSF.
+    /// The integration stops early if the wind loads or the dome seeing time series
+    /// are exhausted, see [GmtOpticalModel::ray_trace_all].
+    ///
+    /// Returns the number of frames, the final PSSn and whether the PSSn has converged
+    pub fn run_until_converged(
+        &mut self,
+        tol: f64,
+        min_frames: usize,
+        max_frames: usize,
+    ) -> (usize, f64, Convergence) {
+        let mut pssn = f64::NAN;
+        let mut n_stable = 0;
+        for n_frame in 1..=max_frames {
+            if self.ray_trace_all().is_none() {
+                return (n_frame - 1, pssn, Convergence::OutOfData);
+            }
+            let next_pssn = self.compute_pssn();
+            if (next_pssn - pssn).abs() < tol {
+                n_stable += 1;
+            } else {
+                n_stable = 0;
+            }
+            pssn = next_pssn;
+            if n_frame >= min_frames && n_stable >= N_CONVERGED {
+                return (n_frame, pssn, Convergence::Converged);
+            }
+        }
+        (max_frames, pssn, Convergence::MaxFrames)
     }
     pub fn compute_pssn(&mut self) -> f64 {
-        self.src.through(&mut self.pssn);
+        self.pssn.through(&mut self.src);
         self.pssn.estimates()[0]
     }
-    pub fn read_detector(&mut self) -> PSF {
+    /// Returns the PSSn of each GMT segment, from segment #1 to segment #7
+    ///
+    /// For each segment, a new source is ray traced through the current state of the telescope
+    /// with all the other segments masked and the current dome seeing and static OPDs are added
+    /// (the pointing jitter is not), the wavefront is then fed to a new PSSn estimator
+    /// with the same band and error model than [GmtOpticalModel::compute_pssn].
+    /// Contrary to [GmtOpticalModel::compute_pssn], the segment PSSn is not cumulated over
+    /// the frames; neither the time series nor the on-axis source and the detector are affected.
+    pub fn compute_segment_pssn(&mut self) -> Result<[f64; 7]> {
+        let mut segment_pssn = [f64::NAN; 7];
+        let mut src = Source::builder()
+            .band(self.builder.photometry.as_str())
+            .build()?;
+        let estimators = (0..7)
+            .map(|_| self.builder.build_pssn().map(|(pssn, _)| pssn))
+            .collect::<Result<Vec<_>>>()?;
+        for (sid, mut pssn) in (1..=7).zip(estimators) {
+            self.gmt.keep(&[sid]);
+            src.through(&mut self.gmt).xpupil();
+            if let Some(opd) = self.domeseeing_opd.as_ref() {
+                src.add(opd.as_slice());
+            }
+            if let Some(opd) = self.static_opd.as_ref() {
+                src.add(opd.as_slice());
+            }
+            pssn.through(&mut src);
+            segment_pssn[sid as usize - 1] = pssn.estimates()[0];
+        }
+        self.gmt.keep(&[1, 2, 3, 4, 5, 6, 7]);
+        Ok(segment_pssn)
+    }
+    /// Reads out and resets the detector
+    ///
+    /// The Strehl ratio is not computed, see [GmtOpticalModel::compute_strehl],
+    /// so the [reference PSF](GmtOpticalModel::reference_psf) is only ray traced if needed
+    ///
+    /// The detector noise of the configuration, if any, is added to the frame,
+    /// see [Config::detector_noise]
+    ///
+    /// An error is returned if the frame is empty, i.e. if the source has not been
+    /// ray traced since the detector was last read out or reset
+    pub fn read_detector(&mut self) -> Result<PSF> {
+        let psf = self.read_frame();
+        self.finish_readout(psf)
+    }
+    /// Reads out and resets the detector, without the detector noise
+    fn read_frame(&mut self) -> PSF {
         let frame: Vec<f32> = self.imgr.frame().into();
         self.imgr.reset();
-        PSF::new(&self.config, frame)
+        self.frame_to_psf(frame)
+    }
+    /// Builds the PSF of a full detector frame
+    ///
+    /// The frame is clipped at the full well capacity and cropped to the detector window
+    fn frame_to_psf(&self, mut frame: Vec<f32>) -> PSF {
+        if let Some(full_well) = self.full_well {
+            let full_well = full_well as f32;
+            frame.iter_mut().for_each(|x| *x = x.min(full_well));
+        }
+        let n_px = self.builder.detector_size;
+        let (rows, cols) = self.detector_shape;
+        if (rows, cols) == (n_px, n_px) {
+            return PSF::new(&self.config, frame).shape(rows, cols);
+        }
+        let (x0, y0) = self.detector_origin;
+        let window: Vec<f32> = frame
+            .chunks(n_px)
+            .skip(y0)
+            .take(rows)
+            .flat_map(|row| &row[x0..x0 + cols])
+            .copied()
+            .collect();
+        let half = n_px as i32 / 2;
+        PSF::new(&self.config, window)
+            .shape(rows, cols)
+            .optical_axis(half - x0 as i32, half - y0 as i32)
+    }
+    /// Checks that the frame of a PSF is not empty and adds the detector noise
+    fn finish_readout(&mut self, mut psf: PSF) -> Result<PSF> {
+        if psf.frame.iter().all(|&x| x == 0.) {
+            return Err(GmtOpticalModelError::EmptyFrame);
+        }
+        self.add_detector_noise(&mut psf.frame);
+        Ok(psf)
+    }
+    /// Reads out and resets the detectors of the field points
+    ///
+    /// Returns one PSF per field point, in the order of [GmtOpticalModel::with_field_points],
+    /// or none if no field points are set.
+    /// Each PSF is read out as with [GmtOpticalModel::read_detector]: it is clipped at the
+    /// full well capacity, cropped to the detector window and the detector noise is added, if any.
+    ///
+    /// An error is returned if any of the frames is empty
+    pub fn read_detectors(&mut self) -> Result<Vec<PSF>> {
+        let Some(field) = self.field.as_mut() else {
+            return Ok(vec![]);
+        };
+        let frame: Vec<f32> = field.imgr.frame().into();
+        field.imgr.reset();
+        let n_px = self.builder.detector_size;
+        frame
+            .chunks(n_px * n_px)
+            .map(|frame| {
+                let psf = self.frame_to_psf(frame.to_vec());
+                self.finish_readout(psf)
+            })
+            .collect()
+    }
+    /// Seeds the random number generator of the detector noise
+    ///
+    /// The generator is seeded from the operating system by default
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.seed = Some(seed);
+        self.update_metadata();
+        self
+    }
+    fn add_detector_noise(&mut self, frame: &mut [f32]) {
+        if let Some(noise) = self.config.detector_noise.as_ref() {
+            add_detector_noise(frame, noise, &mut self.rng);
+        }
+    }
+    /// Saturates the detector at the full well capacity
+    ///
+    /// The pixels of the frame read out, i.e. of the exposure integrated on the detector
+    /// since the previous read, are clipped at `full_well` in the detector frame units
+    /// before any normalization, flattening the core of bright PSFs.
+    /// The PSF peak and the Strehl ratio are computed from the clipped frame.
+    pub fn with_full_well(&mut self, full_well: f64) -> &mut Self {
+        self.full_well = Some(full_well);
+        self.reference = None;
+        self
+    }
+    /// Reads out only a rectangular window of the detector
+    ///
+    /// The window is `width` pixels wide and `height` pixels high with the top left corner
+    /// at pixel (`x0`,`y0`) of the detector.
+    /// The circle overlays remain centered on the optical axis.
+    pub fn detector_window(
+        &mut self,
+        x0: usize,
+        y0: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<&mut Self> {
+        let n_px = self.builder.detector_size;
+        if width == 0 || height == 0 || x0 + width > n_px || y0 + height > n_px {
+            return Err(GmtOpticalModelError::DetectorWindow(
+                x0, y0, width, height, n_px,
+            ));
+        }
+        self.detector_origin = (x0, y0);
+        self.detector_shape = (height, width);
+        self.reference = None;
+        Ok(self)
+    }
+    /// Reads out and resets the detector, binning the frame by `bin`×`bin` pixels on the host
+    ///
+    /// The PSF configuration is rescaled accordingly, the collections of binned frames
+    /// should be created with the same rescaled configuration,
+    /// e.g. `PSFs::new(&gmt.get_config().binned(bin))`
+    pub fn read_detector_binned(&mut self, bin: usize) -> Result<PSF> {
+        Ok(self.read_detector()?.bin(bin))
+    }
+    /// Reads out a super-resolved PSF from sub-pixel dithers of the source
+    ///
+    /// For each (tip,tilt) offset in milli-arcseconds, following the same convention
+    /// as the pointing jitter, the source is ray traced through the current state of the
+    /// telescope and of the dome seeing (the time series are not moved forward), tilted by
+    /// the offset and the detector is read out.
+    /// The dithered frames are drizzle-combined onto a grid oversampled by
+    /// `round(sqrt(offsets_mas.len()))` on each axis, e.g. 2 for a 2×2 dither pattern
+    /// with offsets of half a pixel.
+    ///
+    /// The pixel scale of the super-resolved PSF is the detector pixel scale divided by
+    /// the oversampling factor and its configuration is rescaled with [Config::oversampled].
+    /// The fine pixel values are normalized to conserve the flux of a single detector frame.
+    ///
+    /// The detector is reset beforehand, discarding any accumulated frame.
+    /// An error is returned if `offsets_mas` is empty.
+    pub fn dithered_read(&mut self, offsets_mas: &[(f64, f64)]) -> Result<PSF> {
+        if offsets_mas.is_empty() {
+            return Err(GmtOpticalModelError::NoDither);
+        }
+        let oversampling = ((offsets_mas.len() as f64).sqrt().round() as usize).max(1);
+        let px = self.imgr.pixel_scale(&self.src).to_mas();
+        let n = self.src.pupil_sampling as usize;
+        self.imgr.reset();
+        let mut dithers = Vec::with_capacity(offsets_mas.len());
+        for &(tip, tilt) in offsets_mas {
+            self.src.through(&mut self.gmt).xpupil();
+            if let Some(opd) = self.domeseeing_opd.as_ref() {
+                self.src.add(opd.as_slice());
+            }
+            self.add_static_opd_to_wavefront();
+            let opd = tilt_opd(n, self.src.pupil_size, tip, tilt);
+            self.src.add(opd.as_slice());
+            self.src.through(&mut self.imgr);
+            dithers.push((self.read_detector()?, (tip / px, tilt / px)));
+        }
+        Ok(drizzle(
+            &dithers,
+            oversampling,
+            self.config.clone().oversampled(oversampling),
+        ))
+    }
+    /// Returns the reference PSF, i.e. the on-axis PSF without dome seeing
+    ///
+    /// The reference PSF is computed on first use by ray tracing a new on-axis source
+    /// through the telescope without the wind loads rigid body motions and reading out
+    /// the detector, skipping the dome seeing, the static OPD and the pointing jitter;
+    /// the time series are not moved forward and the wavefront of the model source is kept.
+    /// It is computed again after the photometric band, the detector window or the full well
+    /// capacity are changed.
+    ///
+    /// The detector is reset when the reference PSF is computed, discarding any accumulated frame.
+    pub fn reference_psf(&mut self) -> Result<&PSF> {
+        if self.reference.is_none() {
+            let mut src = Source::builder()
+                .band(self.builder.photometry.as_str())
+                .build()?;
+            if self.rbms.is_some() {
+                let zeros = [0f64; N_MIRROR_RBM];
+                let zeros = Some(zeros.as_slice());
+                self.gmt.update42(zeros, zeros, None, None);
+            }
+            self.imgr.reset();
+            src.through(&mut self.gmt).xpupil();
+            src.through(&mut self.imgr);
+            if let Some(rbms) = self.rbms.as_ref() {
+                let (m1_rbms, m2_rbms) = rbms.split_at(N_MIRROR_RBM);
+                self.gmt.update42(Some(m1_rbms), Some(m2_rbms), None, None);
+            }
+            self.reference = Some(self.read_frame());
+        }
+        Ok(self.reference.as_ref().unwrap())
+    }
+    /// Returns the Strehl ratio of a PSF
+    ///
+    /// The Strehl ratio is the ratio of the peak of the PSF to the peak of the
+    /// [reference PSF](GmtOpticalModel::reference_psf), computed on first use.
+    /// Both peaks are normalized to the flux of their frame: for a frame read out after
+    /// a single ray trace, the source and the reference source are identical and collect
+    /// the same flux, so this is exactly `peak(PSF)/peak(reference)`, while it stays valid
+    /// for frames integrated over several ray traces.
+    /// The PSF must not be binned, use [PSF::strehl_value] to assign it to the PSF.
+    pub fn compute_strehl(&mut self, psf: &PSF) -> Result<f64> {
+        Ok(peak_to_flux(psf) / peak_to_flux(self.reference_psf()?))
+    }
+    /// Returns the Strehl ratio at each (x,y) field angle of `grid` in arcseconds
+    ///
+    /// For each field point, the source is moved off-axis and ray traced through the current
+    /// state of the telescope and of the dome seeing (the time series are not moved forward,
+    /// the dome seeing OPD map is assumed to be the same across the field).
+    /// The Strehl ratio is the ratio of the peak of the PSF normalized to its flux
+    /// to the same ratio for the [reference PSF](GmtOpticalModel::reference_psf).
+    ///
+    /// The frames are read out without the detector noise.
+    ///
+    /// The source is put back on-axis and the detector is reset afterwards, even on error.
+    pub fn strehl_field(&mut self, grid: &[(f64, f64)]) -> Result<Vec<f64>> {
+        let reference_peak = peak_to_flux(self.reference_psf()?);
+        self.imgr.reset();
+        let on_axis = Source::builder()
+            .band(self.builder.photometry.as_str())
+            .build()?;
+        let on_axis = std::mem::replace(&mut self.src, on_axis);
+        let strehl = grid
+            .iter()
+            .map(|&(x, y)| -> Result<f64> {
+                self.src = Source::builder()
+                    .band(self.builder.photometry.as_str())
+                    .zenith_azimuth(
+                        vec![x.hypot(y).from_arcsec() as f32],
+                        vec![y.atan2(x) as f32],
+                    )
+                    .build()?;
+                self.src.through(&mut self.gmt).xpupil();
+                if let Some(opd) = self.domeseeing_opd.as_ref() {
+                    self.src.add(opd.as_slice());
+                }
+                self.add_static_opd_to_wavefront();
+                self.src.through(&mut self.imgr);
+                Ok(peak_to_flux(&self.read_frame()) / reference_peak)
+            })
+            .collect();
+        self.src = on_axis;
+        strehl
+    }
+    /// Returns the (rows,columns) shape of the detector frame
+    pub fn detector_shape(&self) -> (usize, usize) {
+        self.detector_shape
+    }
+    /// Returns the GMT aperture in the exit pupil, `true` inside the aperture
+    ///
+    /// The mask is the non-zero wavefront amplitude of a new on-axis source ray traced through
+    /// the current state of the telescope only, without any turbulence, on the square grid
+    /// of [pupil_sampling](Self::pupil_sampling) by [pupil_sampling](Self::pupil_sampling)
+    /// samples in row-major order like [get_opd](Self::get_opd).
+    /// The wavefront of the imaging source is left untouched.
+    pub fn pupil_mask(&mut self) -> Result<Vec<bool>> {
+        let amplitude: Vec<f32> = self.trace_pupil()?.amplitude().into();
+        Ok(amplitude.into_iter().map(|a| a > 0.).collect())
+    }
+    /// Ray traces a new on-axis source through the current state of the telescope
+    /// to the exit pupil
+    fn trace_pupil(&mut self) -> Result<Source> {
+        let mut src = Source::builder()
+            .band(self.builder.photometry.as_str())
+            .build()?;
+        src.through(&mut self.gmt).xpupil();
+        Ok(src)
+    }
+    /// Saves the wavefront amplitude and phase in the exit pupil side by side to a png image
+    ///
+    /// A new on-axis source is ray traced through the current state of the telescope only,
+    /// without any turbulence, leaving the wavefront of the imaging source untouched.
+    /// The amplitude (left panel) is rendered with the PSF colormap and the phase (right panel)
+    /// with the OPD colormap, normalized to the phase range within the pupil.
+    pub fn save_pupil_png(&mut self, path: impl AsRef<FilePath>) -> Result<()> {
+        let mut src = self.trace_pupil()?;
+        let amplitude: Vec<f32> = src.amplitude().into();
+        let phase = src.phase().as_slice();
+        let n = self.pupil_sampling();
+        let (min_val, max_val) = phase
+            .iter()
+            .zip(&amplitude)
+            .filter(|(_, a)| **a > 0.)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), (&p, _)| {
+                (min.min(p), max.max(p))
+            });
+        let phase_rgb = opd_to_rgb(phase, min_val, max_val, &self.config);
+        let max_amplitude = amplitude.iter().cloned().fold(0f32, f32::max);
+        let image = RgbImage::from_fn(2 * n as u32, n as u32, |x, y| {
+            let (x, y) = (x as usize, y as usize);
+            if x < n {
+                let value = if max_amplitude > 0. {
+                    (amplitude[y * n + x] / max_amplitude) as f64
+                } else {
+                    0.
+                };
+                Rgb(self.config.psf_rgb(value))
+            } else {
+                let k = 3 * (y * n + x - n);
+                if amplitude[y * n + x - n] > 0. {
+                    Rgb([phase_rgb[k], phase_rgb[k + 1], phase_rgb[k + 2]])
+                } else {
+                    Rgb([0, 0, 0])
+                }
+            }
+        });
+        image
+            .save(&path)
+            .map_err(|e| GmtOpticalModelError::SavePupil(e, path.as_ref().to_path_buf()))
     }
     pub fn get_opd(&self) -> &[f32] {
         self.src.phase().as_slice()
     }
+    /// Returns the statistics of the wavefront OPD in nanometers, see [OpdStats]
+    ///
+    /// The statistics are computed over the pupil, i.e. the non-zero finite samples of
+    /// [get_opd](Self::get_opd), and [N_ZERNIKE] Zernike polynomials are fitted to the OPD.
+    /// Returns `None` before the 1st ray trace
+    pub fn opd_stats(&self) -> Option<OpdStats> {
+        let opd_nm: Vec<f32> = self.get_opd().iter().map(|x| x * 1e9).collect();
+        OpdStats::new(&opd_nm, N_ZERNIKE)
+    }
+}
+/// Fried parameter in meters at the wavelength `wavelength` matching the structure function
+/// of the OPD map `opd` sampled on a `n`×`n` grid with `delta` meters spacing
+fn effective_r0(opd: &[f64], n: usize, delta: f64, wavelength: f64) -> Option<f64> {
+    if opd.len() != n * n {
+        return None;
+    }
+    let inside = |x: f64| x.is_finite() && x != 0.;
+    let k = 2. * std::f64::consts::PI / wavelength;
+    let r0s: Vec<f64> = (1..=n / 8)
+        .filter_map(|lag| {
+            let (mut sum, mut count) = (0f64, 0usize);
+            for i in 0..n {
+                for j in 0..n - lag {
+                    for (a, b) in [
+                        (opd[i * n + j], opd[i * n + j + lag]),
+                        (opd[j * n + i], opd[(j + lag) * n + i]),
+                    ] {
+                        if inside(a) && inside(b) {
+                            sum += (k * (a - b)).powi(2);
+                            count += 1;
+                        }
+                    }
+                }
+            }
+            (count > 0 && sum > 0.).then(|| {
+                let d = sum / count as f64;
+                lag as f64 * delta * (6.88 / d).powf(0.6)
+            })
+        })
+        .collect();
+    (!r0s.is_empty()).then(|| r0s.iter().sum::<f64>() / r0s.len() as f64)
+}
+
+/// Ratio of the peak of the PSF to its flux
+fn peak_to_flux(psf: &PSF) -> f64 {
+    let (peak, flux) = psf.frame.iter().fold((0f64, 0f64), |(peak, flux), &x| {
+        (peak.max(x as f64), flux + x as f64)
+    });
+    peak / flux
 }
+
+/// Drizzles the frames shifted by (rows,columns) offsets in pixels onto a grid `oversampling` finer
+///
+/// Each detector pixel is dropped, registered back by its offset, into the fine pixel
+/// containing its center and the fine pixels are averaged over the drops they received
+fn drizzle(dithers: &[(PSF, (f64, f64))], oversampling: usize, config: Arc<Config>) -> PSF {
+    let (rows, cols) = dithers[0].0.shape;
+    let (f_rows, f_cols) = (rows * oversampling, cols * oversampling);
+    let s = oversampling as f64;
+    let mut sum = vec![0f32; f_rows * f_cols];
+    let mut weight = vec![0u32; f_rows * f_cols];
+    for (psf, (di, dj)) in dithers {
+        for (k, value) in psf.frame.iter().enumerate() {
+            let i = (((k / cols) as f64 + 0.5 - di) * s).floor();
+            let j = (((k % cols) as f64 + 0.5 - dj) * s).floor();
+            if i < 0. || j < 0. || i >= f_rows as f64 || j >= f_cols as f64 {
+                continue;
+            }
+            let q = i as usize * f_cols + j as usize;
+            sum[q] += value;
+            weight[q] += 1;
+        }
+    }
+    let area = (oversampling * oversampling) as f32;
+    let frame: Vec<f32> = sum
+        .into_iter()
+        .zip(weight)
+        .map(|(sum, weight)| {
+            if weight > 0 {
+                sum / (weight as f32 * area)
+            } else {
+                0f32
+            }
+        })
+        .collect();
+    let psf = PSF::new(&config, frame).shape(f_rows, f_cols);
+    match dithers[0].0.optical_axis {
+        Some((x, y)) => psf.optical_axis(x * oversampling as i32, y * oversampling as i32),
+        None => psf,
+    }
+}
+
 impl From<&GmtOpticalModel> for PSFs {
     fn from(gmt: &GmtOpticalModel) -> Self {
         Self::new(&gmt.config)
     }
 }
+impl From<&GmtOpticalModel> for LongExposure {
+    fn from(gmt: &GmtOpticalModel) -> Self {
+        Self::new(&gmt.config)
+    }
+}
@@ -0,0 +1,54 @@
+use super::{GmtOpticalModel, Result};
+use crate::PSF;
+
+/// Iterator of the PSF frames of a [GmtOpticalModel]
+///
+/// Created with [GmtOpticalModel::frames], the iterator mutably borrows the model
+/// for as long as it is alive.
+/// Each call to `next` moves the wind loads and the dome seeing time series forward,
+/// ray traces the source and reads out the detector; the frame comes with the OPD map
+/// and the cumulative PSSn, or the error reading out the detector.
+/// The iterator ends after the requested number of frames or as soon as the
+/// wind loads or the dome seeing time series are exhausted.
+/// The wind loads and the dome seeing are applied only if they are enabled,
+/// see [GmtOpticalModel::enable_windloads] and [GmtOpticalModel::enable_domeseeing].
+pub struct Frames<'a> {
+    model: &'a mut GmtOpticalModel,
+    remaining: usize,
+}
+
+impl<'a> Frames<'a> {
+    pub(super) fn new(model: &'a mut GmtOpticalModel, n_frame: usize) -> Self {
+        Self {
+            model,
+            remaining: n_frame,
+        }
+    }
+    /// Returns the optical model, e.g. to compute the Strehl ratio of the latest frame
+    /// with [GmtOpticalModel::compute_strehl]
+    pub fn model(&mut self) -> &mut GmtOpticalModel {
+        self.model
+    }
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Result<PSF>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let model = &mut *self.model;
+        model.ray_trace_all()?;
+        self.remaining -= 1;
+        Some(
+            model
+                .read_detector()
+                .map(|psf| psf.opd(model.get_opd()).pssn_value(model.compute_pssn())),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
@@ -0,0 +1,57 @@
+use rand::{SeedableRng, rngs::StdRng};
+use rand_distr::{Distribution, Normal, NormalError};
+use skyangle::Conversion;
+
+#[derive(Debug, thiserror::Error)]
+pub enum JitterError {
+    #[error("invalid jitter RMS")]
+    Rms(#[from] NormalError),
+}
+type Result<T> = std::result::Result<T, JitterError>;
+
+/// Random pointing jitter
+///
+/// The tip and tilt angles are drawn independently from a Gaussian distribution
+/// with zero mean and the given RMS.
+/// The realized offsets are recorded in milli-arcseconds.
+pub struct Jitter {
+    rng: StdRng,
+    normal: Normal<f64>,
+    offsets: Vec<(f64, f64)>,
+}
+impl Jitter {
+    pub fn new(rms_mas: f64, seed: u64) -> Result<Self> {
+        Ok(Self {
+            rng: StdRng::seed_from_u64(seed),
+            normal: Normal::new(0., rms_mas)?,
+            offsets: vec![],
+        })
+    }
+    /// Returns the realized (tip,tilt) offsets in milli-arcseconds
+    pub fn offsets(&self) -> &[(f64, f64)] {
+        &self.offsets
+    }
+    /// Returns the tilted wavefront across a square pupil of `n` by `n` samples
+    /// and of size `d` in meters, for the next random (tip,tilt) offsets
+    pub fn opd(&mut self, n: usize, d: f64) -> Vec<f64> {
+        let tip = self.normal.sample(&mut self.rng);
+        let tilt = self.normal.sample(&mut self.rng);
+        self.offsets.push((tip, tilt));
+        tilt_opd(n, d, tip, tilt)
+    }
+}
+
+/// Returns the wavefront across a square pupil of `n` by `n` samples and of size `d` in meters,
+/// tilted by the (tip,tilt) angles in milli-arcseconds
+pub(super) fn tilt_opd(n: usize, d: f64, tip_mas: f64, tilt_mas: f64) -> Vec<f64> {
+    let (tip, tilt) = (tip_mas.from_mas(), tilt_mas.from_mas());
+    let delta = d / (n - 1) as f64;
+    let o = 0.5 * (n - 1) as f64;
+    (0..n * n)
+        .map(|k| {
+            let x = ((k / n) as f64 - o) * delta;
+            let y = ((k % n) as f64 - o) * delta;
+            tip * x + tilt * y
+        })
+        .collect()
+}
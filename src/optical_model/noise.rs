@@ -0,0 +1,27 @@
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Normal, Poisson};
+
+use crate::DetectorNoise;
+
+/// Adds the photon and read noise to a detector frame
+///
+/// The frame is assumed to be in photo-electrons per second and is scaled
+/// by the exposure time to the mean number of photo-electrons per pixel.
+/// Each pixel is then Poisson sampled if the photon noise is enabled
+/// and Gaussian read noise is added, the frame is left in electrons.
+pub(super) fn add_detector_noise(frame: &mut [f32], noise: &DetectorNoise, rng: &mut StdRng) {
+    let read_noise = Normal::new(0., noise.read_noise_e)
+        .ok()
+        .filter(|_| noise.read_noise_e > 0.);
+    for x in frame.iter_mut() {
+        let mean = *x as f64 * noise.exposure_s;
+        let mut electrons = match Poisson::new(mean) {
+            Ok(poisson) if noise.photon => poisson.sample(rng),
+            _ => mean,
+        };
+        if let Some(read_noise) = read_noise.as_ref() {
+            electrons += read_noise.sample(rng);
+        }
+        *x = electrons as f32;
+    }
+}
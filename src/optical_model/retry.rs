@@ -0,0 +1,78 @@
+use std::{error::Error, future::Future, time::Duration};
+
+/// Retry policy of the object store reads
+///
+/// Only transient object store errors (throttling, server errors, network failures)
+/// are retried, with an exponential backoff.
+/// Missing objects, invalid paths and denied permissions fail immediately.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the 1st attempt
+    pub max_retries: usize,
+    /// Delay before the 1st retry, doubled after each retry
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between retries
+    pub max_backoff: Duration,
+}
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+impl RetryPolicy {
+    /// No retry
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+    /// Calls `f` until it succeeds, fails with an error that is not retryable
+    /// or the number of retries is exhausted
+    pub(crate) async fn retry<T, E, F, Fut>(&self, mut f: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Error + 'static,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_retries && is_retryable(&e) => {
+                    attempt += 1;
+                    log::warn!(
+                        "object store read failed ({}), retrying in {:?} ({}/{})",
+                        e,
+                        backoff,
+                        attempt,
+                        self.max_retries
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Looks for an object store error in the error chain and checks if it is transient
+///
+/// Throttling and server errors are reported by the object stores as
+/// [object_store::Error::Generic] errors
+fn is_retryable(e: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(e);
+    while let Some(e) = source {
+        if let Some(e) = e.downcast_ref::<object_store::Error>() {
+            return matches!(e, object_store::Error::Generic { .. });
+        }
+        source = e.source();
+    }
+    false
+}
@@ -2,17 +2,41 @@ use gmt_lom::{LinearOpticalModelError, RigidBodyMotions, Table};
 use nalgebra::DMatrix;
 use object_store::{ObjectStore, path::Path};
 
+use super::RetryPolicy;
+use crate::WindLoadsMetadata;
+
 #[derive(Debug, thiserror::Error)]
 pub enum WindLoadsError {
     #[error("failed to load M1 & M1 rigid body motion time series")]
     Loading(#[from] LinearOpticalModelError),
+    #[error("expected {} M1 & M2 rigid body motions, found {0}", N_RBM)]
+    UnexpectedShape(usize),
+    #[error("M1 & M2 rigid body motion time series {1} not found")]
+    NotFound(#[source] object_store::Error, Path),
+    #[error("cannot downsample the rigid body motions from {0}Hz to {1}Hz")]
+    Sampling(usize, usize),
 }
 type Result<T> = std::result::Result<T, WindLoadsError>;
 
+/// Number of rigid body motions of either M1 or M2: 7 segments × 6 DOF
+pub const N_MIRROR_RBM: usize = 42;
+/// Number of M1 & M2 rigid body motions
+pub const N_RBM: usize = 2 * N_MIRROR_RBM;
+
+/// M1 & M2 rigid body motions time series
+///
+/// Each sample is a vector of [N_RBM] rigid body motions, the [N_MIRROR_RBM] M1 RBMs
+/// followed by the [N_MIRROR_RBM] M2 RBMs.
+/// The RBMs of a mirror are ordered by segment (#1 to #7) and, for each segment,
+/// as (Tx,Ty,Tz,Rx,Ry,Rz).
 pub struct WindLoads {
     rbms: DMatrix<f64>,
+    path: Path,
+    input_hz: usize,
     step: usize,
+    skip: usize,
     count: usize,
+    gain: f64,
 }
 impl WindLoads {
     // M1 & M2 RBMs iterator `N_SAMPLE` @ 5Hz
@@ -21,19 +45,115 @@ impl WindLoads {
     // The 1st 5s (5000 samples) are skipped and the RBMs are
     // downsampled by a factor 1000Hz/5Hz=200
     pub async fn new(storage: impl ObjectStore, path: impl Into<Path>) -> Result<Self> {
-        let table = Table::from_stored_parquet(storage, path.into()).await?;
+        Self::with_sampling(storage, path, 1000, 5, 5.).await
+    }
+    /// Loads the M1 & M2 RBMs sampled at `input_hz` and downsamples them to `output_hz`
+    ///
+    /// The first `skip_seconds` of the time series, the ramp up of the wind loads,
+    /// are skipped.
+    /// An error is returned if `input_hz` is not a multiple of `output_hz`.
+    pub async fn with_sampling(
+        storage: impl ObjectStore,
+        path: impl Into<Path>,
+        input_hz: usize,
+        output_hz: usize,
+        skip_seconds: f64,
+    ) -> Result<Self> {
+        if output_hz == 0 || input_hz == 0 || input_hz % output_hz != 0 {
+            return Err(WindLoadsError::Sampling(input_hz, output_hz));
+        }
+        let skip = (input_hz as f64 * skip_seconds.max(0.)).round() as usize;
+        let path: Path = path.into();
+        let table = Table::from_stored_parquet(storage, path.clone()).await?;
         let rbms = RigidBodyMotions::from_table(
             &table,
             Some("M1RigidBodyMotions"),
             Some("M2RigidBodyMotions"),
         )?
         .into_data();
+        if rbms.nrows() != N_RBM {
+            return Err(WindLoadsError::UnexpectedShape(rbms.nrows()));
+        }
         Ok(Self {
             rbms,
-            step: 200,
-            count: 5000,
+            path,
+            input_hz,
+            step: input_hz / output_hz,
+            skip,
+            count: skip,
+            gain: 1.,
         })
     }
+    /// Same as [WindLoads::new] but first checking that the parquet file exists
+    pub async fn new_checked(storage: impl ObjectStore, path: impl Into<Path>) -> Result<Self> {
+        let path: Path = path.into();
+        storage
+            .head(&path)
+            .await
+            .map_err(|e| WindLoadsError::NotFound(e, path.clone()))?;
+        Self::new(storage, path).await
+    }
+    /// Same as [WindLoads::new] but retrying transient object store failures
+    pub async fn new_with_retry(
+        storage: impl ObjectStore + Clone,
+        path: impl Into<Path>,
+        policy: &RetryPolicy,
+    ) -> Result<Self> {
+        let path: Path = path.into();
+        policy
+            .retry(|| Self::new(storage.clone(), path.clone()))
+            .await
+    }
+}
+impl WindLoads {
+    /// Moves the iterator to the given downsampled sample, after the skipped samples
+    pub fn seek_sample(&mut self, sample: usize) {
+        self.count = self.skip + sample * self.step;
+    }
+    /// Moves the iterator to the sample nearest to `seconds` from the start of the time series
+    ///
+    /// The skipped samples are ignored, a negative time moves the iterator to the first sample
+    /// and a time beyond the end of the time series ends the iteration.
+    pub fn seek(&mut self, seconds: f64) {
+        let sample = (seconds.max(0.) * self.input_hz as f64).round();
+        self.count = (sample as usize).min(self.rbms.ncols());
+    }
+    /// Returns the time in seconds, from the start of the time series, of the next sample
+    pub fn position(&self) -> f64 {
+        self.count as f64 / self.input_hz as f64
+    }
+    /// Returns the number of downsampled samples of the time series, after the skipped samples
+    pub fn n_samples(&self) -> usize {
+        self.rbms
+            .ncols()
+            .saturating_sub(self.skip)
+            .div_ceil(self.step)
+    }
+    /// Returns the sampling rate in Hz of the downsampled time series
+    pub fn output_hz(&self) -> f64 {
+        self.input_hz as f64 / self.step as f64
+    }
+    /// Returns the wind loads settings recorded in the model metadata
+    pub(crate) fn metadata(&self, enabled: bool) -> WindLoadsMetadata {
+        WindLoadsMetadata {
+            path: self.path.to_string(),
+            input_hz: self.input_hz,
+            output_hz: self.output_hz(),
+            skip_seconds: self.skip as f64 / self.input_hz as f64,
+            gain: self.gain,
+            enabled,
+        }
+    }
+    /// Scales the rigid body motions by `gain`
+    ///
+    /// A gain of 0 removes the motions while keeping the time sampling
+    pub fn with_gain(self, gain: f64) -> Self {
+        Self { gain, ..self }
+    }
+    /// Sets the rigid body motions gain
+    pub fn set_gain(&mut self, gain: f64) {
+        self.gain = gain;
+    }
 }
 impl Iterator for WindLoads {
     type Item = Box<[f64]>;
@@ -42,9 +162,22 @@ impl Iterator for WindLoads {
         let i = self.count;
         if i < self.rbms.ncols() {
             self.count += self.step;
-            Some(self.rbms.column(i).as_slice().into())
+            Some(self.rbms.column(i).iter().map(|x| x * self.gain).collect())
         } else {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.len();
+        (n, Some(n))
+    }
+}
+impl ExactSizeIterator for WindLoads {
+    fn len(&self) -> usize {
+        self.rbms
+            .ncols()
+            .saturating_sub(self.count)
+            .div_ceil(self.step)
+    }
 }
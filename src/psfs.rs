@@ -13,19 +13,38 @@ with shared configuration and batch processing capabilities.
 */
 
 use std::{
-    fs::create_dir_all,
-    io,
+    fs::{File, create_dir_all},
+    io::{self, BufWriter, Write},
     path::{Path, PathBuf},
     sync::Arc,
     sync::atomic::AtomicUsize,
+    time::Duration,
 };
 
+use image::{
+    Delay, DynamicImage, Frame, ImageBuffer, ImageError, Rgb, RgbImage,
+    codecs::gif::{GifEncoder, Repeat},
+    imageops::{self, FilterType},
+};
+use imageproc::{drawing, rect::Rect};
 use indicatif::ProgressBar;
+use object_store::{ObjectStore, PutPayload, path::Path as ObjectPath};
 
-use crate::{Config, DETECTOR_SIZE, psfs::psf::PSFError};
+use crate::{Config, DETECTOR_SIZE, ModelMetadata, StorePath, config::ConfigError};
 
+mod fits;
+mod long_exposure;
+mod mtf;
+mod npy;
+mod opd;
 mod psf;
-pub use psf::PSF;
+pub use long_exposure::LongExposure;
+pub use mtf::MtfData;
+pub use opd::{N_ZERNIKE, OpdStats};
+use psf::histogram_bins;
+#[cfg(any(feature = "ssr", test))]
+pub(crate) use psf::opd_to_rgb;
+pub use psf::{PSF, PSFError};
 
 /// Errors that can occur during PSF collection operations
 #[derive(Debug, thiserror::Error)]
@@ -36,6 +55,90 @@ pub enum PSFsError {
     /// Failed to process or save individual PSF frame
     #[error("failed to save a frame")]
     PsfError(#[from] PSFError),
+    /// Frame index out of range
+    #[error("frame index {0} is out of range, valid range is 0..{1}")]
+    FrameIndex(usize, usize),
+    /// Failed to create the run metadata file
+    #[error("failed to create metadata file {1:?}")]
+    CreateMetadata(#[source] io::Error, PathBuf),
+    /// Failed to write the run metadata to JSON
+    #[error("failed to write metadata to {1:?}")]
+    WriteMetadata(#[source] serde_json::Error, PathBuf),
+    /// Failed to serialize the run metadata to JSON
+    #[error("failed to serialize metadata")]
+    SerializeMetadata(#[from] serde_json::Error),
+    /// Failed to create RGB image buffer from PSF data
+    #[error("failed to create image buffer")]
+    Image,
+    /// Failed to draw the contact sheet labels
+    #[error("failed to draw labels")]
+    Config(#[from] ConfigError),
+    /// Failed to save the contact sheet image
+    #[error("failed to save contact sheet to {1:?}")]
+    SaveContactSheet(#[source] ImageError, PathBuf),
+    /// Failed to put an object into the object store
+    #[error("failed to put {1} into the object store")]
+    Store(#[source] object_store::Error, ObjectPath),
+    /// Failed to write the NumPy cube
+    #[error("failed to write NumPy cube to {1:?}")]
+    SaveNpy(#[source] io::Error, PathBuf),
+    /// Failed to write the FITS cube
+    #[error("failed to write FITS cube to {1:?}")]
+    SaveFits(#[source] io::Error, PathBuf),
+    /// The frames of two collections have different shapes
+    #[error("cannot merge {1:?} frames into {0:?} frames")]
+    ShapeMismatch((usize, usize), (usize, usize)),
+    /// The frames of two collections are rendered with different configurations
+    #[error("cannot merge frames rendered with a different {0}")]
+    ConfigMismatch(&'static str),
+    /// None of the frames has a PSSN value
+    #[error("no frame has a PSSn value")]
+    NoPssn,
+    /// The animation frame rate is not strictly positive
+    #[error("expected a strictly positive animation frame rate, found {0}")]
+    FrameRate(f64),
+    /// Failed to create the animation file
+    #[error("failed to create animation file {1:?}")]
+    CreateAnimation(#[source] io::Error, PathBuf),
+    /// Failed to encode the animation
+    #[error("failed to encode animation to {1:?}")]
+    EncodeAnimation(#[source] ImageError, PathBuf),
+    /// Failed to write the per-frame metrics CSV file
+    #[error("failed to write metrics to {1:?}")]
+    WriteMetricsCsv(#[source] io::Error, PathBuf),
+    /// Failed to save the PSSN histogram image
+    #[error("failed to save PSSn histogram to {1:?}")]
+    SavePssnHistogram(#[source] ImageError, PathBuf),
+}
+
+/// Run metadata saved alongside the PSF frames for provenance
+#[derive(Debug, serde::Serialize)]
+pub struct RunMetadata<'a> {
+    /// Version of this crate
+    pub version: &'static str,
+    /// CFD case
+    pub cfd_case: Option<&'a str>,
+    /// Turbulence effects (dome seeing and/or wind loads)
+    pub turbulence_effects: Option<&'a str>,
+    /// Observation wavelength in nanometers
+    pub wavelength_nm: f64,
+    /// Number of frames
+    pub n_frame: usize,
+    /// Optical model settings
+    pub model: Option<&'a ModelMetadata>,
+}
+impl RunMetadata<'_> {
+    /// Save the run metadata as `run_metadata.json` into the given directory
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PSFsError> {
+        let dir = Path::new(path.as_ref());
+        create_dir_all(dir).map_err(|e| PSFsError::CreateFrameDir(e, dir.to_path_buf()))?;
+        let filename = dir.join("run_metadata.json");
+        let file =
+            File::create(&filename).map_err(|e| PSFsError::CreateMetadata(e, filename.clone()))?;
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|e| PSFsError::WriteMetadata(e, filename))?;
+        Ok(())
+    }
 }
 
 /// Collection of PSF frames with shared configuration and batch processing
@@ -45,7 +148,7 @@ pub enum PSFsError {
 ///
 /// # Example
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 /// use psf::{Config, PSFs};
 ///
 /// let config = Config::new(50.0, 25.0, 500.0);
@@ -61,9 +164,27 @@ pub enum PSFsError {
 #[derive(Debug, Default)]
 pub struct PSFs {
     psfs: Vec<PSF>,
+    pssn_values: Vec<f64>,
     config: Arc<Config>,
+    merged: bool,
 }
 
+/// Long exposure normalization
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum NormMode {
+    /// Sum of the frames, conserves the total flux of the exposure
+    #[default]
+    Sum,
+    /// Mean of the frames, conserves the flux of a single frame
+    Mean,
+}
+
+/// Size in pixels of the contact sheet thumbnails
+pub const CONTACT_SHEET_TILE: usize = 96;
+
+/// (width,height) in pixels of the PSSN histogram image
+pub const PSSN_HISTOGRAM_SIZE: (u32, u32) = (400, 250);
+
 /// Find global minimum and maximum values across all frames for consistent normalization
 ///
 /// # Parameters
@@ -99,11 +220,27 @@ impl PSFs {
 
     /// Add a new PSF frame to the collection with automatic numbering
     ///
+    /// If the `psf::frame` log target is enabled at the info level, a record is logged
+    /// with the frame index, PSSn, OPD RMS and centroid as `key=value` pairs
+    ///
     /// # Parameters
     ///
-    /// - `psf` - Raw PSF intensity data as flat vector (DETECTOR_SIZE²)
+    /// - `psf` - PSF frame
     pub fn push(&mut self, psf: PSF) {
         let i = self.psfs.len();
+        if log::log_enabled!(target: "psf::frame", log::Level::Info) {
+            let centroid = psf.centroid().unwrap_or((f64::NAN, f64::NAN));
+            log::info!(
+                target: "psf::frame",
+                "frame_index={} pssn={} opd_rms={} centroid_x={:.3} centroid_y={:.3}",
+                i,
+                psf.pssn_value.unwrap_or(f64::NAN),
+                psf.opd_rms().unwrap_or(f64::NAN),
+                centroid.0,
+                centroid.1
+            );
+        }
+        self.pssn_values.push(psf.pssn_value.unwrap_or(f64::NAN));
         self.psfs.push(psf.frame_number(i));
     }
 
@@ -112,31 +249,381 @@ impl PSFs {
         self.psfs.len()
     }
 
+    /// Check if the collection holds no PSF frame
+    pub fn is_empty(&self) -> bool {
+        self.psfs.is_empty()
+    }
+
+    /// Get the PSSN values of all frames in the order they were pushed
+    ///
+    /// Frames pushed without a PSSN value are reported as `NaN` so that
+    /// the slice index always matches the frame number
+    pub fn pssn_values(&self) -> &[f64] {
+        &self.pssn_values
+    }
+
+    /// Get the frame with the largest PSSN value
+    ///
+    /// Frames without PSSN value are ignored
+    ///
+    /// # Returns
+    ///
+    /// The frame index and the frame, `None` if no frame has a PSSN value
+    pub fn best_pssn(&self) -> Option<(usize, &PSF)> {
+        self.pssn_values
+            .iter()
+            .enumerate()
+            .filter(|(_, pssn)| !pssn.is_nan())
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| (i, &self.psfs[i]))
+    }
+
+    /// Get the frame with the smallest PSSN value
+    ///
+    /// Frames without PSSN value are ignored
+    ///
+    /// # Returns
+    ///
+    /// The frame index and the frame, `None` if no frame has a PSSN value
+    pub fn worst_pssn(&self) -> Option<(usize, &PSF)> {
+        self.pssn_values
+            .iter()
+            .enumerate()
+            .filter(|(_, pssn)| !pssn.is_nan())
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| (i, &self.psfs[i]))
+    }
+
+    /// Get the (rows,columns) shape of the frames
+    ///
+    /// The shape of the collection is the shape of the 1st frame or
+    /// the detector size if the collection is empty
+    pub fn shape(&self) -> (usize, usize) {
+        self.psfs
+            .first()
+            .map_or((DETECTOR_SIZE, DETECTOR_SIZE), |psf| psf.shape)
+    }
+
+    /// Compute the RMS of the frame centroids about their mean, i.e. the image motion
+    ///
+    /// # Returns
+    ///
+    /// The (x,y) RMS of the [centroids](PSF::centroid) in milli-arcseconds
+    /// or `None` if there are no frames with a centroid or if the pixel scale is unknown
+    pub fn centroid_rms(&self) -> Option<(f64, f64)> {
+        let pixel_scale_mas = self.config.model.as_ref()?.pixel_scale_mas;
+        let centroids: Vec<_> = self.psfs.iter().filter_map(|psf| psf.centroid()).collect();
+        if centroids.is_empty() {
+            return None;
+        }
+        let n = centroids.len() as f64;
+        let (mx, my) = centroids
+            .iter()
+            .fold((0., 0.), |(mx, my), (x, y)| (mx + x / n, my + y / n));
+        let (vx, vy) = centroids.iter().fold((0., 0.), |(vx, vy), (x, y)| {
+            (vx + (x - mx).powi(2) / n, vy + (y - my).powi(2) / n)
+        });
+        Some((vx.sqrt() * pixel_scale_mas, vy.sqrt() * pixel_scale_mas))
+    }
+
+    /// Save all the frames as a single FITS cube
+    ///
+    /// The frames are stacked along the 3rd axis of a single precision image,
+    /// the header records the wavelength (`WAVELNTH` in nm), the CFD case (`CFDCASE`),
+    /// the turbulence effects (`TURBEFF`) and the last PSSn value (`PSSN`) if any.
+    /// The raw detector counts are saved, without normalization.
+    pub fn save_fits_cube(&self, path: impl AsRef<Path>) -> Result<(), PSFsError> {
+        let path = path.as_ref();
+        let mut keywords = vec![("WAVELNTH", fits::Value::Float(self.config.wavelength_nm))];
+        if let Some(cfd_case) = self.config.cfd_case.as_deref() {
+            keywords.push(("CFDCASE", fits::Value::Text(cfd_case)));
+        }
+        if let Some(turbulence_effects) = self.config.turbulence_effects.as_deref() {
+            keywords.push(("TURBEFF", fits::Value::Text(turbulence_effects)));
+        }
+        if let Some(pssn) = self.psfs.last().and_then(|psf| psf.pssn_value) {
+            keywords.push(("PSSN", fits::Value::Float(pssn)));
+        }
+        let file = File::create(path).map_err(|e| PSFsError::SaveFits(e, path.to_path_buf()))?;
+        fits::write_cube(
+            io::BufWriter::new(file),
+            self.shape(),
+            self.psfs.iter().map(|psf| psf.frame.as_slice()),
+            &keywords,
+        )
+        .map_err(|e| PSFsError::SaveFits(e, path.to_path_buf()))
+    }
+
+    /// Save each frame as a 2D FITS image
+    ///
+    /// The frames are saved as `frame_XXXXXX.fits` into the given directory,
+    /// see [PSF::save_as_fits]
+    pub fn save_all_fits(&self, path: impl AsRef<Path>) -> Result<(), PSFsError> {
+        let frames_dir = path.as_ref();
+        create_dir_all(frames_dir)
+            .map_err(|e| PSFsError::CreateFrameDir(e, frames_dir.to_path_buf()))?;
+        for (i, psf) in self.psfs.iter().enumerate() {
+            psf.save_as_fits(frames_dir.join(format!("frame_{:06}.fits", i)))?;
+        }
+        Ok(())
+    }
+
+    /// Save all the frames as a single NumPy cube
+    ///
+    /// The frames are saved as `frames.npy` into the given directory, a little-endian
+    /// single precision array of shape (frames,rows,columns) to be loaded with `numpy.load`.
+    /// The raw detector counts are saved, without normalization.
+    pub fn save_all_npy(&self, path: impl AsRef<Path>) -> Result<(), PSFsError> {
+        let dir = path.as_ref();
+        create_dir_all(dir).map_err(|e| PSFsError::CreateFrameDir(e, dir.to_path_buf()))?;
+        let path = dir.join("frames.npy");
+        let (rows, cols) = self.shape();
+        let file = File::create(&path).map_err(|e| PSFsError::SaveNpy(e, path.clone()))?;
+        npy::write_array(
+            io::BufWriter::new(file),
+            &[self.len(), rows, cols],
+            self.psfs.iter().map(|psf| psf.frame.as_slice()),
+        )
+        .map_err(|e| PSFsError::SaveNpy(e, path))
+    }
+
+    /// Append the frames of another collection
+    ///
+    /// The frames of `other` are renumbered after the frames of this collection,
+    /// so the long exposure from [PSFs::sum] spans both collections.
+    /// The PSSn values are kept as they are: they are cumulative within each collection,
+    /// so the PSSn of the merged long exposure is unknown and is not assigned by [PSFs::sum].
+    ///
+    /// # Errors
+    ///
+    /// If the frames of both collections do not have the same shape
+    /// or are not rendered at the same wavelength, detector size and pixel scale,
+    /// accounting for the binning
+    pub fn extend(&mut self, other: PSFs) -> Result<(), PSFsError> {
+        if !self.is_empty() && !other.is_empty() && self.shape() != other.shape() {
+            return Err(PSFsError::ShapeMismatch(self.shape(), other.shape()));
+        }
+        let (config, other_config) = (&self.config, &other.config);
+        if config.wavelength_nm != other_config.wavelength_nm {
+            return Err(PSFsError::ConfigMismatch("wavelength"));
+        }
+        let detector_size = |config: &Config| config.model.as_ref().map(|m| m.detector_size);
+        if detector_size(config) != detector_size(other_config) {
+            return Err(PSFsError::ConfigMismatch("detector size"));
+        }
+        let pixel_scale = |config: &Config| config.model.as_ref().map(|m| m.pixel_scale_mas);
+        if pixel_scale(config) != pixel_scale(other_config) {
+            return Err(PSFsError::ConfigMismatch("pixel scale"));
+        }
+        self.merged |= other.merged || (!self.is_empty() && !other.is_empty());
+        for psf in other.psfs {
+            let i = self.psfs.len();
+            self.pssn_values.push(psf.pssn_value.unwrap_or(f64::NAN));
+            self.psfs.push(psf.frame_number(i));
+        }
+        Ok(())
+    }
+
+    /// Iterate over the PSF frames in the order they were pushed
+    pub fn iter(&self) -> impl Iterator<Item = &PSF> {
+        self.psfs.iter()
+    }
+
     /// Create a summed (long exposure) PSF from all frames in the collection
     ///
+    /// This is the same as `sum_with(NormMode::Sum)`
+    ///
     /// # Returns
     ///
     /// Single PSF representing the sum of all individual frames,
     /// using the PSSN value from the last frame
     pub fn sum(&self) -> PSF {
-        let summed_frame = self.psfs.iter().map(|psf| &psf.frame).fold(
-            vec![0f32; DETECTOR_SIZE.pow(2)],
-            |mut s, f| {
-                s.iter_mut().zip(f.into_iter()).for_each(|(s, f)| {
-                    *s += f;
+        self.sum_with(NormMode::Sum)
+    }
+
+    /// Create a long exposure PSF from all frames in the collection
+    ///
+    /// With [NormMode::Sum], the long exposure holds the total flux of the whole exposure.
+    /// With [NormMode::Mean], the long exposure holds the same flux than each short exposure,
+    /// so it can be rendered with the same normalization than the short exposures
+    /// (see [PSFs::frames_extrema]) and compared to them directly.
+    ///
+    /// # Returns
+    ///
+    /// Single PSF representing the sum or the mean of all individual frames,
+    /// using the PSSN value from the last frame, if any, unless collections have been
+    /// merged with [PSFs::extend]
+    pub fn sum_with(&self, norm: NormMode) -> PSF {
+        let (rows, cols) = self.shape();
+        let summed_frame =
+            self.psfs
+                .iter()
+                .map(|psf| &psf.frame)
+                .fold(vec![0f32; rows * cols], |mut s, f| {
+                    s.iter_mut().zip(f).for_each(|(s, f)| {
+                        *s += f;
+                    });
+                    s
                 });
-                s
-            },
-        );
-        PSF::new(&self.config, summed_frame)
-            .pssn_value(self.psfs.last().and_then(|psf| psf.pssn_value).unwrap())
+        let long_exposure_frame = match norm {
+            NormMode::Sum => summed_frame,
+            NormMode::Mean => {
+                let n = self.len().max(1) as f32;
+                summed_frame.into_iter().map(|x| x / n).collect()
+            }
+        };
+        let mut psf = PSF::new(&self.config, long_exposure_frame)
+            .shape(rows, cols)
+            .long_exposure();
+        if !self.merged {
+            psf.pssn_value = self.psfs.last().and_then(|psf| psf.pssn_value);
+        }
+        psf.optical_axis = self.psfs.first().and_then(|psf| psf.optical_axis);
+        psf
+    }
+    /// Collect the run metadata of the PSF collection
+    pub fn metadata(&self) -> RunMetadata<'_> {
+        RunMetadata {
+            version: env!("CARGO_PKG_VERSION"),
+            cfd_case: self.config.cfd_case.as_deref(),
+            turbulence_effects: self.config.turbulence_effects.as_deref(),
+            wavelength_nm: self.config.wavelength_nm,
+            n_frame: self.len(),
+            model: self.config.model.as_ref(),
+        }
     }
+
+    /// Save the run metadata as `run_metadata.json` into the given directory
+    ///
+    /// The metadata records the CFD case, turbulence effects, wavelength,
+    /// number of frames, optical model settings and crate version
+    pub fn save_metadata(&self, path: impl AsRef<Path>) -> Result<(), PSFsError> {
+        self.metadata().save(path)
+    }
+
+    /// Write the per-frame metrics to a CSV file
+    ///
+    /// The columns are `frame_index,pssn,strehl,peak,centroid_x,centroid_y`,
+    /// the [centroid](PSF::centroid) is given in pixels from the center of the frame
+    /// and the PSSn, Strehl ratio and centroid fields are left empty when unknown
+    pub fn write_metrics_csv(&self, path: impl AsRef<Path>) -> Result<(), PSFsError> {
+        let path = path.as_ref();
+        let write = || -> io::Result<()> {
+            let mut file = BufWriter::new(File::create(path)?);
+            writeln!(file, "frame_index,pssn,strehl,peak,centroid_x,centroid_y")?;
+            for (i, psf) in self.psfs.iter().enumerate() {
+                let field = |value: Option<f64>| value.map_or(String::new(), |v| v.to_string());
+                let (x, y) = psf.centroid().unzip();
+                writeln!(
+                    file,
+                    "{i},{},{},{},{},{}",
+                    field(psf.pssn_value),
+                    field(psf.strehl()),
+                    psf.peak(),
+                    field(x),
+                    field(y)
+                )?;
+            }
+            file.flush()
+        };
+        write().map_err(|e| PSFsError::WriteMetricsCsv(e, path.to_path_buf()))
+    }
+
+    /// Compute the histogram of the intensity of all the frames
+    ///
+    /// The bins are evenly spread over the global range of the frames values.
+    ///
+    /// # Parameters
+    ///
+    /// - `n_bins` - Number of bins
+    ///
+    /// # Returns
+    ///
+    /// Vector of (bin center, count) pairs
+    pub fn global_histogram(&self, n_bins: usize) -> Vec<(f32, u64)> {
+        let (minmax, _) = self.global_extrema();
+        let counts = self
+            .psfs
+            .iter()
+            .map(|psf| psf.histogram_counts(n_bins, minmax))
+            .fold(vec![0u64; n_bins], |mut h, c| {
+                h.iter_mut().zip(c).for_each(|(h, c)| *h += c);
+                h
+            });
+        histogram_bins(counts, minmax)
+    }
+
+    /// Compute the normalized temporal autocorrelation of the frames
+    ///
+    /// The long exposure mean frame is subtracted from each frame and the correlation
+    /// of the residual frames separated by `lag` frames is normalized by the correlation
+    /// at zero lag. A high correlation at lag 1 indicates that the frames are sampled
+    /// too fast relative to the turbulence evolution.
+    ///
+    /// # Parameters
+    ///
+    /// - `max_lag` - Largest lag in number of frames
+    ///
+    /// # Returns
+    ///
+    /// Autocorrelation for lags 0 to `max_lag` (limited to the number of frames minus one),
+    /// empty if there is no frame
+    pub fn frame_autocorrelation(&self, max_lag: usize) -> Vec<f64> {
+        let n = self.len();
+        if n == 0 {
+            return vec![];
+        }
+        let (rows, cols) = self.shape();
+        let mean = self
+            .psfs
+            .iter()
+            .fold(vec![0f64; rows * cols], |mut m, psf| {
+                m.iter_mut()
+                    .zip(&psf.frame)
+                    .for_each(|(m, x)| *m += *x as f64 / n as f64);
+                m
+            });
+        // residuals are computed on the fly to avoid storing a copy of the frames
+        let correlation = |lag: usize| {
+            self.psfs
+                .iter()
+                .zip(self.psfs.iter().skip(lag))
+                .map(|(a, b)| {
+                    a.frame
+                        .iter()
+                        .zip(&b.frame)
+                        .zip(&mean)
+                        .map(|((a, b), m)| (*a as f64 - m) * (*b as f64 - m))
+                        .sum::<f64>()
+                })
+                .sum::<f64>()
+                / (n - lag) as f64
+        };
+        let c0 = correlation(0);
+        (0..=max_lag.min(n - 1))
+            .map(|lag| if c0 > 0. { correlation(lag) / c0 } else { 0. })
+            .collect()
+    }
+
     /// Export all PSF frames as PNG images with global normalization and progress tracking
     ///
     /// Creates a `frames/` directory and saves each PSF as `frame_XXXXXX.png`
     /// with consistent normalization across all frames for proper visualization
     /// of temporal variations. Shows progress bar during export process.
     ///
+    /// With the `rayon` feature, the frames are encoded and written in parallel
+    /// once the global normalization is computed, and the frames are tracked
+    /// as they complete with the count of the frames saved before them,
+    /// so the progress only moves forward whatever the order the frames complete in.
+    ///
+    /// # Parameters
+    ///
+    /// - `path` - Output directory
+    /// - `tracker` - Progress tracker of the frames
+    /// - `global_max` - Optional upper bound of the normalization, e.g. the [PSFs::global_max]
+    ///   of another collection to share the normalization across collections;
+    ///   uses the global maximum of this collection if None
+    ///
     /// # Returns
     ///
     /// Result indicating success or failure of the batch export operation
@@ -144,38 +631,364 @@ impl PSFs {
         &self,
         path: impl AsRef<Path>,
         tracker: impl FrameTracker,
+        global_max: Option<f32>,
     ) -> Result<(), PSFsError> {
-        let frames: Vec<_> = self.psfs.iter().map(|psf| psf.frame.as_slice()).collect();
-        let frames_global_minmax = find_global_extrema(frames.into_iter());
-        let opds: Option<Vec<&[f32]>> = self
-            .psfs
-            .iter()
-            .map(|psf| psf.opd.as_ref().map(|opd| opd.as_slice()))
-            .collect();
-        let ops_global_minmax = opds.map(|opds| find_global_extrema(opds.into_iter()));
+        let ((min, max), opds_global_minmax) = self.global_extrema();
+        let frames_global_minmax = (min, global_max.unwrap_or(max));
 
         // Setup output directory
         let frames_dir = Path::new(path.as_ref());
         create_dir_all(frames_dir)
             .map_err(|e| PSFsError::CreateFrameDir(e, frames_dir.to_path_buf()))?;
 
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            use std::sync::atomic::Ordering;
+            let completed = AtomicUsize::new(0);
+            self.psfs.par_iter().enumerate().try_for_each(|(i, psf)| {
+                Self::save_frame(frames_dir, i, psf, frames_global_minmax, opds_global_minmax)
+                    .map(|()| tracker.track(completed.fetch_add(1, Ordering::Relaxed)))
+            })?;
+        }
+        #[cfg(not(feature = "rayon"))]
         for (i, psf) in self.psfs.iter().enumerate() {
             tracker.track(i);
-            let filename = frames_dir.join(format!("frame_{:06}.png", i));
-            psf.save_frame_as_png(filename, Some(frames_global_minmax))?;
-            if let Err(e) = psf.save_opd_as_png(
-                frames_dir.join(format!("opd_{:06}.png", i)),
-                ops_global_minmax,
-            ) {
-                match e {
-                    PSFError::OpdMissing => (),
-                    _ => return Err(e.into()),
-                }
+            Self::save_frame(frames_dir, i, psf, frames_global_minmax, opds_global_minmax)?;
+        }
+
+        Ok(())
+    }
+
+    /// Export a selection of PSF frames as PNG images with global normalization
+    ///
+    /// The frames are saved as `frame_XXXXXX.png` like with [PSFs::save_all_frames]
+    /// and they are normalized with the extrema of all the frames
+    /// in the collection, not only the selected ones.
+    ///
+    /// # Parameters
+    ///
+    /// - `path` - Output directory
+    /// - `indices` - Indices of the frames to save
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or failure, an error is returned if any of the
+    /// indices is out of range before any frame is saved
+    pub fn save_frames(&self, path: impl AsRef<Path>, indices: &[usize]) -> Result<(), PSFsError> {
+        if let Some(&index) = indices.iter().find(|&&i| i >= self.len()) {
+            return Err(PSFsError::FrameIndex(index, self.len()));
+        }
+        let (frames_global_minmax, opds_global_minmax) = self.global_extrema();
+
+        // Setup output directory
+        let frames_dir = Path::new(path.as_ref());
+        create_dir_all(frames_dir)
+            .map_err(|e| PSFsError::CreateFrameDir(e, frames_dir.to_path_buf()))?;
+
+        for &i in indices {
+            Self::save_frame(
+                frames_dir,
+                i,
+                &self.psfs[i],
+                frames_global_minmax,
+                opds_global_minmax,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Global (min,max) intensity of all the short exposure frames
+    ///
+    /// This is the normalization used by [PSFs::save_all_frames]
+    pub fn frames_extrema(&self) -> (f32, f32) {
+        self.global_extrema().0
+    }
+
+    /// Global maximum intensity of all the short exposure frames
+    ///
+    /// This is the upper bound of the normalization used by [PSFs::save_all_frames]
+    pub fn global_max(&self) -> f32 {
+        self.frames_extrema().1
+    }
+
+    /// Global extrema of the frames and of the OPDs if all the frames have one
+    fn global_extrema(&self) -> ((f32, f32), Option<(f32, f32)>) {
+        let frames: Vec<_> = self.psfs.iter().map(|psf| psf.frame.as_slice()).collect();
+        let frames_global_minmax = find_global_extrema(frames.into_iter());
+        let opds: Option<Vec<&[f32]>> = self.psfs.iter().map(|psf| psf.opd.as_deref()).collect();
+        let opds_global_minmax = opds.map(|opds| find_global_extrema(opds.into_iter()));
+        (frames_global_minmax, opds_global_minmax)
+    }
+
+    /// Save the frame and, if present, the OPD of a PSF into `frames_dir`
+    fn save_frame(
+        frames_dir: &Path,
+        i: usize,
+        psf: &PSF,
+        frames_global_minmax: (f32, f32),
+        opds_global_minmax: Option<(f32, f32)>,
+    ) -> Result<(), PSFsError> {
+        let filename = frames_dir.join(format!("frame_{:06}.png", i));
+        psf.save_frame_as_png(filename, Some(frames_global_minmax))?;
+        if let Err(e) = psf.save_opd_as_png(
+            frames_dir.join(format!("opd_{:06}.png", i)),
+            opds_global_minmax,
+        ) {
+            match e {
+                PSFError::OpdMissing => (),
+                _ => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+    /// Encode all the PSF frames into an animated GIF
+    ///
+    /// The frames are annotated and normalized like with [PSFs::save_all_frames]
+    /// and the animation loops forever.
+    ///
+    /// # Parameters
+    ///
+    /// - `path` - Output GIF file path
+    /// - `fps` - Frame rate of the animation in frames per second
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or failure of the encoding
+    pub fn save_animation(&self, path: impl AsRef<Path>, fps: f64) -> Result<(), PSFsError> {
+        let (minmax, _) = self.global_extrema();
+        self.encode_animation(path.as_ref(), fps, |psf| psf.render_frame(Some(minmax)))
+    }
+
+    /// Encode the OPD maps of all the frames into an animated GIF
+    ///
+    /// Like [PSFs::save_animation] but for the OPD maps, an error is returned
+    /// if any of the frames has no OPD map
+    pub fn save_opd_animation(&self, path: impl AsRef<Path>, fps: f64) -> Result<(), PSFsError> {
+        let (_, opds_minmax) = self.global_extrema();
+        let minmax = opds_minmax.ok_or(PSFError::OpdMissing)?;
+        self.encode_animation(path.as_ref(), fps, |psf| psf.render_opd(Some(minmax)))
+    }
+
+    fn encode_animation(
+        &self,
+        path: &Path,
+        fps: f64,
+        render: impl Fn(&PSF) -> Result<RgbImage, PSFError>,
+    ) -> Result<(), PSFsError> {
+        if !(fps.is_finite() && fps > 0.) {
+            return Err(PSFsError::FrameRate(fps));
+        }
+        let delay = Delay::from_saturating_duration(Duration::from_secs_f64(fps.recip()));
+        let file =
+            File::create(path).map_err(|e| PSFsError::CreateAnimation(e, path.to_path_buf()))?;
+        let mut encoder = GifEncoder::new(BufWriter::new(file));
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| PSFsError::EncodeAnimation(e, path.to_path_buf()))?;
+        for psf in &self.psfs {
+            let image = DynamicImage::ImageRgb8(render(psf)?).to_rgba8();
+            encoder
+                .encode_frame(Frame::from_parts(image, 0, 0, delay))
+                .map_err(|e| PSFsError::EncodeAnimation(e, path.to_path_buf()))?;
+        }
+        Ok(())
+    }
+
+    /// Export a mosaic of thumbnails of all the frames as a PNG image
+    ///
+    /// Each frame is downsampled to a [CONTACT_SHEET_TILE]×[CONTACT_SHEET_TILE] tile
+    /// with global normalization and labeled with its frame index and PSSN value.
+    /// The tiles are laid out row by row.
+    ///
+    /// # Parameters
+    ///
+    /// - `path` - Output PNG file path
+    /// - `cols` - Number of tiles per row
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or rendering/save error
+    pub fn save_contact_sheet(&self, path: impl AsRef<Path>, cols: usize) -> Result<(), PSFsError> {
+        let cols = cols.max(1);
+        let rows = self.len().div_ceil(cols);
+        let tile = CONTACT_SHEET_TILE as u32;
+        let mut sheet = RgbImage::new(cols as u32 * tile, rows as u32 * tile);
+
+        let (min_val, max_val) = self.global_extrema().0;
+        for (i, psf) in self.psfs.iter().enumerate() {
+            let (rows, cols) = psf.shape;
+            let image = ImageBuffer::<Rgb<u8>, Vec<u8>>::from_raw(
+                cols as u32,
+                rows as u32,
+                psf.frame_to_rgb(min_val, max_val),
+            )
+            .ok_or(PSFsError::Image)?;
+            let mut thumbnail = imageops::resize(&image, tile, tile, FilterType::Triangle);
+            self.config
+                .draw_label(&mut thumbnail, (2, 2), 11., &format!("{:03}", i))?;
+            if let Some(pssn) = psf.pssn_value {
+                self.config.draw_label(
+                    &mut thumbnail,
+                    (2, tile as i32 - 13),
+                    11.,
+                    &format!("{:.4}", pssn),
+                )?;
             }
+            if let Some(enclosure) = psf.enclosure {
+                self.config
+                    .draw_enclosure_badge(&mut thumbnail, enclosure)?;
+            }
+            let (x, y) = ((i % cols) as u32 * tile, (i / cols) as u32 * tile);
+            imageops::replace(&mut sheet, &thumbnail, x as i64, y as i64);
         }
 
+        sheet
+            .save(&path)
+            .map_err(|e| PSFsError::SaveContactSheet(e, path.as_ref().to_path_buf()))?;
         Ok(())
     }
+
+    /// Export the histogram of the PSSN values of the frames as a PNG image
+    ///
+    /// The bins are evenly spread over the range of the PSSN values and the
+    /// mean and standard deviation of the PSSN values are written on top of the histogram.
+    /// Frames without PSSN value are ignored.
+    ///
+    /// # Parameters
+    ///
+    /// - `path` - Output PNG file path
+    /// - `n_bins` - Number of bins
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or rendering/save error
+    pub fn save_pssn_histogram(
+        &self,
+        path: impl AsRef<Path>,
+        n_bins: usize,
+    ) -> Result<(), PSFsError> {
+        let values: Vec<f64> = self
+            .pssn_values
+            .iter()
+            .cloned()
+            .filter(|x| x.is_finite())
+            .collect();
+        if values.is_empty() {
+            return Err(PSFsError::NoPssn);
+        }
+        let n_bins = n_bins.max(1);
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std = (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt();
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let width = if max > min { max - min } else { 1e-6 };
+        let mut counts = vec![0u64; n_bins];
+        for x in &values {
+            let i = (((x - min) / width) * n_bins as f64) as usize;
+            counts[i.min(n_bins - 1)] += 1;
+        }
+
+        let (w, h) = PSSN_HISTOGRAM_SIZE;
+        let (top, bottom, margin) = (30u32, 20u32, 10u32);
+        let mut image = RgbImage::new(w, h);
+        let bar_width = (w - 2 * margin) as f32 / n_bins as f32;
+        let max_count = counts.iter().cloned().max().unwrap_or(1).max(1) as f32;
+        let plot_height = (h - top - bottom) as f32;
+        for (i, &count) in counts.iter().enumerate() {
+            let bar_height = (plot_height * count as f32 / max_count).round() as u32;
+            if bar_height == 0 {
+                continue;
+            }
+            let x = margin as f32 + i as f32 * bar_width;
+            drawing::draw_filled_rect_mut(
+                &mut image,
+                Rect::at(x.round() as i32, (h - bottom - bar_height) as i32)
+                    .of_size((bar_width - 1.).max(1.) as u32, bar_height),
+                Rgb([200u8, 200u8, 200u8]),
+            );
+        }
+        self.config.draw_label(
+            &mut image,
+            (margin as i32, 5),
+            15.,
+            &format!("PSSn mean: {:.4}, std: {:.4}", mean, std),
+        )?;
+        self.config.draw_label(
+            &mut image,
+            (margin as i32, (h - 17) as i32),
+            13.,
+            &format!("{:.4}", min),
+        )?;
+        self.config.draw_label(
+            &mut image,
+            ((w - margin - 45) as i32, (h - 17) as i32),
+            13.,
+            &format!("{:.4}", max),
+        )?;
+
+        image
+            .save(&path)
+            .map_err(|e| PSFsError::SavePssnHistogram(e, path.as_ref().to_path_buf()))?;
+        Ok(())
+    }
+
+    /// Upload all PSF frames as PNG images to an object store with global normalization
+    ///
+    /// The PNG images are encoded in memory and put into the store under `prefix`
+    /// as `frame_XXXXXX.png` (and `opd_XXXXXX.png` if the frames have OPDs),
+    /// together with `long_exposure_psf.png` and `run_metadata.json`.
+    ///
+    /// # Parameters
+    ///
+    /// - `store` - Object store to write to
+    /// - `prefix` - Object store path the objects are written under
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or failure of the batch upload
+    pub async fn save_all_frames_to_store(
+        &self,
+        store: Arc<dyn ObjectStore>,
+        prefix: ObjectPath,
+    ) -> Result<(), PSFsError> {
+        let (frames_global_minmax, opds_global_minmax) = self.global_extrema();
+
+        for (i, psf) in self.psfs.iter().enumerate() {
+            let png = psf.encode_frame_as_png(Some(frames_global_minmax))?;
+            Self::put(&store, prefix.join(format!("frame_{:06}.png", i)), png).await?;
+            match psf.encode_opd_as_png(opds_global_minmax) {
+                Ok(png) => Self::put(&store, prefix.join(format!("opd_{:06}.png", i)), png).await?,
+                Err(PSFError::OpdMissing) => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if !self.is_empty() {
+            let png = self.sum().encode_frame_as_png(None)?;
+            Self::put(&store, prefix.join("long_exposure_psf.png"), png).await?;
+        }
+
+        let metadata = serde_json::to_vec_pretty(&self.metadata())?;
+        Self::put(&store, prefix.join("run_metadata.json"), metadata).await?;
+
+        Ok(())
+    }
+
+    async fn put(
+        store: &Arc<dyn ObjectStore>,
+        location: ObjectPath,
+        bytes: Vec<u8>,
+    ) -> Result<(), PSFsError> {
+        store
+            .put(&location, PutPayload::from(bytes))
+            .await
+            .map_err(|e| PSFsError::Store(e, location))?;
+        Ok(())
+    }
+
     // pub fn save_all_frames_with_atomic_index(
     //     &self,
     //     path: impl AsRef<Path>,
@@ -199,7 +1012,7 @@ impl PSFs {
     // }
 }
 
-pub trait FrameTracker {
+pub trait FrameTracker: Sync {
     fn track(&self, i: usize);
 }
 impl FrameTracker for &ProgressBar {
@@ -212,3 +1025,484 @@ impl FrameTracker for &AtomicUsize {
         self.store(i, std::sync::atomic::Ordering::Relaxed);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Colormap, MockOpticalModel, Normalization, OpticalModel};
+
+    #[test]
+    fn psfs_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Config>();
+        assert_send_sync::<PSF>();
+        assert_send_sync::<PSFs>();
+        assert_send_sync::<LongExposure>();
+    }
+
+    fn psfs(n_frame: usize) -> PSFs {
+        let mut model = MockOpticalModel::new(32, 2.);
+        let mut psfs = PSFs::new(&model.get_config());
+        for _ in 0..n_frame {
+            let psf = model.ray_trace().read_detector().unwrap();
+            let pssn = model.compute_pssn();
+            psfs.push(psf.pssn_value(pssn));
+        }
+        psfs
+    }
+
+    #[test]
+    fn reading_an_empty_detector_fails() {
+        let mut model = MockOpticalModel::new(32, 2.);
+        assert!(matches!(model.read_detector(), Err(PSFError::EmptyFrame)));
+        assert!(model.ray_trace().read_detector().is_ok());
+        assert!(model.read_detector().is_err());
+    }
+
+    #[test]
+    fn config_json_round_trip() {
+        let config = Config::new(40., 8., 550.)
+            .cfd_case("zen30az000_OS7")
+            .turbulence_effects("Dome Seeing")
+            .colormap(Colormap::Inferno)
+            .normalization(Normalization::Asinh { softening: 0.1 });
+        let path =
+            std::env::temp_dir().join(format!("gmt-cfd-psf_config_{}.json", std::process::id()));
+        config.to_json(&path).unwrap();
+        let loaded = Config::from_json(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            serde_json::to_string(&*loaded).unwrap(),
+            serde_json::to_string(&*config).unwrap()
+        );
+        assert_eq!(loaded.wavelength_nm, 550.);
+        assert_eq!(loaded.overlays.len(), 2);
+    }
+
+    #[test]
+    fn animation_has_one_frame_per_psf() {
+        use image::{AnimationDecoder, codecs::gif::GifDecoder};
+        let psfs = psfs(3);
+        let path =
+            std::env::temp_dir().join(format!("gmt-cfd-psf_animation_{}.gif", std::process::id()));
+        psfs.save_animation(&path, 5.).unwrap();
+        let decoder = GifDecoder::new(io::BufReader::new(File::open(&path).unwrap())).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(
+            Duration::from(frames[0].delay()),
+            Duration::from_millis(200)
+        );
+        assert!(matches!(
+            psfs.save_animation(&path, 0.),
+            Err(PSFsError::FrameRate(_))
+        ));
+    }
+
+    #[test]
+    fn sum_conserves_flux() {
+        let psfs = psfs(10);
+        let total: f32 = psfs.iter().flat_map(|psf| psf.frame.iter()).sum();
+        let long_exposure: f32 = psfs.sum().frame.iter().sum();
+        assert!((total - long_exposure).abs() / total < 1e-5);
+        assert_eq!(psfs.sum().get_shape(), (32, 32));
+    }
+
+    #[test]
+    fn long_exposure_png_bytes() {
+        let bytes = psfs(2).sum().to_png_bytes().unwrap();
+        assert!(bytes.starts_with(b"\x89PNG"));
+    }
+
+    #[test]
+    fn binning_conserves_flux() {
+        let psf = psfs(1).sum();
+        let total: f32 = psf.frame.iter().sum();
+        let binned = psf.bin(4);
+        assert_eq!(binned.get_shape(), (8, 8));
+        assert!((total - binned.frame.iter().sum::<f32>()).abs() / total < 1e-5);
+    }
+
+    #[test]
+    fn long_exposure_without_pssn() {
+        let mut model = MockOpticalModel::new(32, 2.);
+        let mut psfs = PSFs::new(&model.get_config());
+        psfs.push(model.ray_trace().read_detector().unwrap());
+        assert!(psfs.sum().pssn().is_none());
+    }
+
+    #[test]
+    fn difference_of_flux_normalized_psfs() {
+        let psfs = psfs(2);
+        let psf = psfs.sum();
+        let difference = psf.difference(psfs.iter().next().unwrap()).unwrap();
+        assert!(difference.frame.iter().sum::<f32>().abs() < 1e-5);
+        assert!(difference.signed);
+        assert!(psf.difference(&psfs.sum().bin(2)).is_none());
+    }
+
+    #[test]
+    fn pssn_histogram_png() {
+        let path = std::env::temp_dir().join(format!(
+            "gmt-cfd-psf_pssn_histogram_{}.png",
+            std::process::id()
+        ));
+        psfs(10).save_pssn_histogram(&path, 5).unwrap();
+        let dimensions = image::image_dimensions(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(dimensions, PSSN_HISTOGRAM_SIZE);
+        assert!(matches!(
+            PSFs::new(&Config::new(1., 1., 500.)).save_pssn_histogram(&path, 5),
+            Err(PSFsError::NoPssn)
+        ));
+    }
+
+    #[test]
+    fn fits_frame_header_and_size() {
+        let dir =
+            std::env::temp_dir().join(format!("gmt-cfd-psf_fits_frames_{}", std::process::id()));
+        let psfs = psfs(2);
+        psfs.save_all_fits(&dir).unwrap();
+        let bytes = std::fs::read(dir.join("frame_000001.fits")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let (rows, cols) = psfs.shape();
+        let n_data = 4 * rows * cols;
+        assert_eq!(bytes.len(), 2880 + n_data.div_ceil(2880) * 2880);
+        let header = String::from_utf8_lossy(&bytes[..2880]);
+        assert!(header.contains("NAXIS   =                    2"));
+        assert!(header.contains("PSSN    ="));
+        assert!(!header.contains("NAXIS3"));
+    }
+
+    #[test]
+    fn encircled_energy_of_a_gaussian() {
+        let sigma = 4.;
+        let mut model = MockOpticalModel::new(64, sigma).wander(0., 1);
+        model.set_config(model.get_config().model(ModelMetadata {
+            pixel_scale_mas: 10.,
+            ..Default::default()
+        }));
+        let psf = model.ray_trace().read_detector().unwrap();
+        for fraction in [0.5, 0.8] {
+            let expected = 10. * sigma * (-2. * (1f64 - fraction).ln()).sqrt();
+            let radius = psf.encircled_energy(fraction).unwrap();
+            assert!((radius - expected).abs() / expected < 0.05);
+        }
+        assert!(psf.encircled_energy(0.).is_err());
+        assert!(psf.encircled_energy(1.5).is_err());
+    }
+
+    #[test]
+    fn radial_profile_of_a_gaussian() {
+        let sigma = 4.;
+        let mut model = MockOpticalModel::new(64, sigma).wander(0., 1);
+        let psf = model.ray_trace().read_detector().unwrap();
+        assert!(matches!(psf.radial_profile(16), Err(PSFError::PixelScale)));
+        model.set_config(model.get_config().model(ModelMetadata {
+            pixel_scale_mas: 10.,
+            ..Default::default()
+        }));
+        let psf = model.ray_trace().read_detector().unwrap();
+        let profile = psf.radial_profile(16).unwrap();
+        assert_eq!(profile.len(), 16);
+        assert!(profile.windows(2).all(|w| w[0].0 < w[1].0));
+        let core: Vec<_> = profile
+            .iter()
+            .take_while(|(radius, _)| *radius < 10. * 3. * sigma)
+            .collect();
+        assert!(core.windows(2).all(|w| w[0].1 > w[1].1));
+        assert!(psf.radial_profile(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn centroid_rms_of_a_wandering_psf() {
+        let (radius, period) = (3., 20);
+        let mut model = MockOpticalModel::new(64, 2.).wander(radius, period);
+        model.set_config(model.get_config().model(ModelMetadata {
+            pixel_scale_mas: 10.,
+            ..Default::default()
+        }));
+        let mut psfs = PSFs::new(&model.get_config());
+        for _ in 0..period {
+            psfs.push(model.ray_trace().read_detector().unwrap());
+        }
+        let expected = 10. * radius / 2f64.sqrt();
+        let (x, y) = psfs.centroid_rms().unwrap();
+        assert!((x - expected).abs() / expected < 0.01);
+        assert!((y - expected).abs() / expected < 0.01);
+        assert!(
+            PSFs::new(&Config::new(1., 1., 500.))
+                .centroid_rms()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn colorbar_widens_the_frame() {
+        let psf = psfs(1).sum();
+        let (rows, cols) = psf.shape;
+        let image = psf.render_frame(None).unwrap();
+        assert_eq!(image.dimensions(), (cols as u32, rows as u32));
+        let psf = PSF {
+            config: psf.config.clone().show_colorbar(true),
+            ..psf
+        };
+        let image = psf.render_frame(None).unwrap();
+        assert_eq!(
+            image.dimensions(),
+            (cols as u32 + crate::COLORBAR_WIDTH, rows as u32)
+        );
+    }
+
+    #[test]
+    fn signed_difference_is_centered_on_zero() {
+        let psfs = psfs(2);
+        let mut frames = psfs.iter();
+        let (a, b) = (frames.next().unwrap(), frames.next().unwrap());
+        let diff = a.diff(b).unwrap();
+        for ((d, a), b) in diff.as_slice().iter().zip(a.as_slice()).zip(b.as_slice()) {
+            assert_eq!(*d, a - b);
+        }
+        let zero = a.diff(a).unwrap();
+        assert!(a.diff(&psfs.sum().bin(2)).is_none());
+        assert!(zero.as_slice().iter().all(|x| *x == 0.));
+        let image = zero.render_raw_frame(None).unwrap();
+        assert!(image.pixels().all(|p| p.0 == zero.config.signed_rgb(0.5)));
+    }
+
+    #[test]
+    fn normalization_stretch_and_inverse() {
+        for normalization in [
+            Normalization::Linear,
+            Normalization::Log,
+            Normalization::Asinh { softening: 0.05 },
+        ] {
+            assert_eq!(normalization.stretch(0.), 0.);
+            assert!((normalization.stretch(1.) - 1.).abs() < 1e-12);
+            for value in [0.001, 0.1, 0.5] {
+                let stretched = normalization.stretch(value);
+                assert!((normalization.inverse(stretched) - value).abs() < 1e-9);
+                if normalization != Normalization::Linear {
+                    assert!(stretched > value);
+                }
+            }
+        }
+        let psf = psfs(1).sum();
+        let linear = psf.render_raw_frame(None).unwrap();
+        let psf = PSF {
+            config: psf.config.clone().normalization(Normalization::Log),
+            ..psf
+        };
+        assert_ne!(psf.render_raw_frame(None).unwrap(), linear);
+    }
+
+    #[test]
+    fn colormaps_of_a_gradient() {
+        let gradient = |colormap| {
+            let frame: Vec<f32> = (0..256).map(|k| k as f32).collect();
+            PSF::new(&Config::new(1., 1., 500.).colormap(colormap), frame).frame_to_rgb(0., 255.)
+        };
+        for (colormap, first, last) in [
+            (Colormap::Gray, [0, 0, 0], [255, 255, 255]),
+            (Colormap::Viridis, [68, 1, 84], [253, 231, 37]),
+            (Colormap::Inferno, [0, 0, 4], [252, 255, 164]),
+        ] {
+            let rgb = gradient(colormap);
+            assert_eq!(rgb[..3], first);
+            assert_eq!(rgb[rgb.len() - 3..], last);
+        }
+        let rgb = gradient(Colormap::Turbo);
+        let color = colorous::TURBO.eval_continuous((128f32 / 255.) as f64);
+        assert_eq!(rgb[3 * 128..3 * 128 + 3], [color.r, color.g, color.b]);
+        let config = Config::new(1., 1., 500.).colormap(Colormap::Turbo);
+        let opd = opd_to_rgb(&[0., 1.], 0., 1., &config);
+        assert_eq!(opd[..3], Colormap::Turbo.rgb(0.));
+    }
+
+    #[test]
+    fn zernike_fit_of_tip_and_focus() {
+        let n = 64;
+        let opd: Vec<f32> = (0..n * n)
+            .map(|k| {
+                let half = (n - 1) as f64 / 2.;
+                let (x, y) = ((k % n) as f64 / half - 1., (k / n) as f64 / half - 1.);
+                let r2 = x * x + y * y;
+                if r2 > 1. {
+                    0.
+                } else {
+                    (0.5 * 2. * x + 0.25 * 3f64.sqrt() * (2. * r2 - 1.) + 1.) as f32
+                }
+            })
+            .collect();
+        let stats = OpdStats::new(&opd, N_ZERNIKE).unwrap();
+        assert_eq!(
+            stats.valid_pixels,
+            opd.iter().filter(|x| x.is_finite() && **x != 0.).count()
+        );
+        let c = stats.zernike;
+        for (c, e) in c.iter().zip([1., 0.5, 0., 0.25, 0., 0.]) {
+            assert!((c - e).abs() < 1e-4, "{c} vs {e}");
+        }
+    }
+
+    /// Renders the long exposure of the mock frames and compares it against
+    /// `tests/golden/mock_long_exposure.png`
+    ///
+    /// Run with `BLESS=1` to regenerate the golden image after an intended rendering change
+    #[test]
+    fn golden_long_exposure_image() {
+        let golden =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/mock_long_exposure.png");
+        let rendered =
+            std::env::temp_dir().join(format!("gmt-cfd-psf_golden_{}.png", std::process::id()));
+        psfs(8).sum().save(&rendered).unwrap();
+        if std::env::var("BLESS").is_ok_and(|value| value == "1") {
+            create_dir_all(golden.parent().unwrap()).unwrap();
+            std::fs::copy(&rendered, &golden).unwrap();
+            return;
+        }
+        let expected = image::open(&golden)
+            .unwrap_or_else(|e| panic!("failed to open the golden image {golden:?}: {e}"))
+            .to_rgb8();
+        let actual = image::open(&rendered).unwrap().to_rgb8();
+        std::fs::remove_file(&rendered).ok();
+        assert_eq!(expected.dimensions(), actual.dimensions());
+        let n_diff = expected
+            .pixels()
+            .zip(actual.pixels())
+            .filter(|(e, a)| e.0.iter().zip(a.0.iter()).any(|(e, a)| e.abs_diff(*a) > 2))
+            .count();
+        assert!(
+            n_diff * 1000 <= expected.len() / 3,
+            "{n_diff} pixels differ from the golden image {}, run with BLESS=1 if the change is intended",
+            golden.display()
+        );
+    }
+
+    #[test]
+    fn extend_concatenates_frames() {
+        let mut merged = psfs(3);
+        merged.extend(psfs(2)).unwrap();
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged.pssn_values().len(), 5);
+        assert_eq!(merged.iter().last().unwrap().frame_number, Some(4));
+        assert!(merged.sum().pssn().is_none());
+
+        let config = merged.config.clone().model(ModelMetadata {
+            detector_size: 32,
+            pixel_scale_mas: 10.,
+            ..Default::default()
+        });
+        assert!(matches!(
+            PSFs::new(&config).extend(PSFs::new(&config.clone().binned(2))),
+            Err(PSFsError::ConfigMismatch("detector size"))
+        ));
+
+        let mut model = MockOpticalModel::new(16, 2.);
+        let mut other = PSFs::new(&model.get_config());
+        other.push(model.ray_trace().read_detector().unwrap());
+        assert!(matches!(
+            merged.extend(other),
+            Err(PSFsError::ShapeMismatch((32, 32), (16, 16)))
+        ));
+    }
+
+    #[test]
+    fn npy_cube_header_and_size() {
+        let psfs = psfs(3);
+        let dir = std::env::temp_dir().join(format!("gmt-cfd-psf_npy_{}", std::process::id()));
+        psfs.save_all_npy(&dir).unwrap();
+        let bytes = std::fs::read(dir.join("frames.npy")).unwrap();
+        assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+        let (rows, cols) = psfs.shape();
+        assert!(header.contains(&format!("'shape': (3, {rows}, {cols})")));
+        assert!(header.ends_with('\n'));
+        let data = &bytes[10 + header_len..];
+        assert_eq!(data.len(), 3 * rows * cols * 4);
+        let first = f32::from_le_bytes(data[..4].try_into().unwrap());
+        assert_eq!(first, psfs.iter().next().unwrap().as_slice()[0]);
+    }
+
+    #[test]
+    fn fits_cube_is_block_aligned() {
+        let path =
+            std::env::temp_dir().join(format!("gmt-cfd-psf_cube_{}.fits", std::process::id()));
+        psfs(3).save_fits_cube(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(bytes.len() % 2880, 0);
+        assert!(bytes.starts_with(b"SIMPLE  =                    T"));
+        assert!(bytes.len() >= 2880 + 3 * 32 * 32 * 4);
+    }
+
+    #[test]
+    fn mean_within_frames_extrema() {
+        let psfs = psfs(10);
+        let (min, max) = psfs.frames_extrema();
+        let mean = psfs.sum_with(NormMode::Mean);
+        let (mean_min, mean_max) = find_global_extrema(std::iter::once(mean.frame.as_slice()));
+        assert!(mean_min >= min && mean_max <= max);
+        assert_eq!(psfs.global_max(), max);
+    }
+
+    #[test]
+    fn pssn_values_match_frames() {
+        let psfs = psfs(10);
+        assert_eq!(psfs.pssn_values().len(), psfs.len());
+        let (best, _) = psfs.best_pssn().unwrap();
+        let (worst, _) = psfs.worst_pssn().unwrap();
+        assert!(psfs.pssn_values()[worst] <= psfs.pssn_values()[best]);
+        assert!(PSFs::default().best_pssn().is_none());
+    }
+
+    #[test]
+    fn metrics_csv_has_one_row_per_frame() {
+        let psfs = psfs(3);
+        let path =
+            std::env::temp_dir().join(format!("gmt-cfd-psf_metrics_{}.csv", std::process::id()));
+        psfs.write_metrics_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("frame_index,pssn,strehl,peak,centroid_x,centroid_y")
+        );
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), psfs.len());
+        for (i, (row, psf)) in rows.iter().zip(psfs.iter()).enumerate() {
+            assert_eq!(row.len(), 6);
+            assert_eq!(row[0], i.to_string());
+            assert_eq!(row[3].parse::<f32>().unwrap(), psf.peak());
+        }
+    }
+
+    #[test]
+    fn global_extrema() {
+        let psfs = psfs(5);
+        let ((min, max), opd_minmax) = psfs.global_extrema();
+        assert!(min >= 0. && max <= 1.);
+        assert!(opd_minmax.is_none());
+    }
+
+    #[test]
+    fn histogram_counts_all_pixels() {
+        let psfs = psfs(3);
+        let h = psfs.global_histogram(16);
+        assert_eq!(h.len(), 16);
+        assert_eq!(h.iter().map(|(_, c)| c).sum::<u64>(), 3 * 32 * 32);
+    }
+
+    #[test]
+    fn autocorrelation_at_zero_lag() {
+        let psfs = psfs(20);
+        let c = psfs.frame_autocorrelation(3);
+        assert_eq!(c.len(), 4);
+        assert!((c[0] - 1.).abs() < 1e-12);
+        assert!(c[1] < 1.);
+    }
+}
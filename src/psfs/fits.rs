@@ -0,0 +1,100 @@
+/*!
+# FITS writer
+
+Minimal writer of the PSF frames as single precision FITS primary HDUs:
+either a 2D image of a single frame or a 3D image of all the frames
+stacked along the 3rd axis.
+*/
+
+use std::io::{self, Write};
+
+/// FITS block size in bytes
+const BLOCK: usize = 2880;
+/// FITS header card size in bytes
+const CARD: usize = 80;
+
+/// FITS header card value
+#[derive(Clone, Copy)]
+pub(crate) enum Value<'a> {
+    Logical(bool),
+    Integer(i64),
+    Float(f64),
+    Text(&'a str),
+}
+
+fn card(keyword: &str, value: Value) -> String {
+    let value = match value {
+        Value::Logical(value) => format!("{:>20}", if value { "T" } else { "F" }),
+        Value::Integer(value) => format!("{:>20}", value),
+        Value::Float(value) => format!("{:>20}", format!("{:E}", value)),
+        Value::Text(value) => format!("'{:<8}'", value.replace('\'', "''")),
+    };
+    let mut card = format!("{:<8}= {}", keyword, value);
+    card.truncate(CARD);
+    format!("{:<80}", card)
+}
+
+/// Writes a 3D single precision FITS image
+///
+/// The frames are `rows`×`cols` row-major images and `keywords` are
+/// additional header cards
+pub(crate) fn write_cube<'a, W: Write>(
+    writer: W,
+    (rows, cols): (usize, usize),
+    frames: impl ExactSizeIterator<Item = &'a [f32]>,
+    keywords: &[(&str, Value)],
+) -> io::Result<()> {
+    let n_frame = frames.len();
+    write_image(writer, &[cols, rows, n_frame], frames, keywords)
+}
+
+/// Writes a 2D single precision FITS image
+///
+/// The frame is a `rows`×`cols` row-major image and `keywords` are
+/// additional header cards
+pub(crate) fn write_frame<W: Write>(
+    writer: W,
+    (rows, cols): (usize, usize),
+    frame: &[f32],
+    keywords: &[(&str, Value)],
+) -> io::Result<()> {
+    write_image(writer, &[cols, rows], std::iter::once(frame), keywords)
+}
+
+/// Writes a single precision FITS image with the given axes length, the fastest varying first
+fn write_image<'a, W: Write>(
+    mut writer: W,
+    naxes: &[usize],
+    frames: impl Iterator<Item = &'a [f32]>,
+    keywords: &[(&str, Value)],
+) -> io::Result<()> {
+    let mut header = [
+        card("SIMPLE", Value::Logical(true)),
+        card("BITPIX", Value::Integer(-32)),
+        card("NAXIS", Value::Integer(naxes.len() as i64)),
+    ]
+    .concat();
+    for (i, &naxis) in naxes.iter().enumerate() {
+        header.push_str(&card(
+            &format!("NAXIS{}", i + 1),
+            Value::Integer(naxis as i64),
+        ));
+    }
+    for (keyword, value) in keywords {
+        header.push_str(&card(keyword, *value));
+    }
+    header.push_str(&format!("{:<80}", "END"));
+    let padding = (BLOCK - header.len() % BLOCK) % BLOCK;
+    header.push_str(&" ".repeat(padding));
+    writer.write_all(header.as_bytes())?;
+
+    let mut n_byte = 0;
+    for frame in frames {
+        let data: Vec<u8> = frame.iter().flat_map(|x| x.to_be_bytes()).collect();
+        writer.write_all(&data)?;
+        n_byte += data.len();
+    }
+    let padding = (BLOCK - n_byte % BLOCK) % BLOCK;
+    writer.write_all(&vec![0u8; padding])?;
+    writer.flush()
+}
@@ -0,0 +1,110 @@
+/*!
+# Long Exposure Accumulator
+
+This module provides the [`LongExposure`] type for integrating PSF frames
+into a long exposure PSF without storing the individual frames.
+*/
+
+use std::{path::Path, sync::Arc};
+
+use super::{PSFsError, RunMetadata};
+use crate::{Config, PSF};
+
+/// Streaming accumulator of PSF frames into a long exposure PSF
+///
+/// Contrary to [`PSFs`](crate::PSFs), the frames are summed as they are added
+/// and then dropped, so the memory footprint does not grow with the number of frames.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use psf::{GmtOpticalModel, LongExposure};
+///
+/// let mut gmt = GmtOpticalModel::new()?;
+/// let mut long_exposure = LongExposure::from(&gmt);
+/// for _ in 0..5000 {
+///     let psf = gmt.ray_trace().read_detector()?.pssn_value(gmt.compute_pssn());
+///     long_exposure.add(psf);
+/// }
+/// long_exposure.psf().save("long_exposure_psf.png")?;
+/// ```
+#[derive(Debug, Default)]
+pub struct LongExposure {
+    frame: Vec<f32>,
+    shape: (usize, usize),
+    n_frame: usize,
+    pssn_value: Option<f64>,
+    optical_axis: Option<(i32, i32)>,
+    config: Arc<Config>,
+}
+impl LongExposure {
+    /// Create a new long exposure accumulator with shared configuration
+    pub fn new(config: &Arc<Config>) -> Self {
+        Self {
+            config: config.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Add a PSF frame to the long exposure
+    ///
+    /// The PSSN value of the frame, if any, replaces the previous one
+    /// as PSSN values are cumulative
+    pub fn add(&mut self, psf: PSF) {
+        if self.frame.is_empty() {
+            self.frame = vec![0f32; psf.frame.len()];
+            self.shape = psf.shape;
+            self.optical_axis = psf.optical_axis;
+        }
+        self.frame
+            .iter_mut()
+            .zip(psf.frame.iter())
+            .for_each(|(s, f)| *s += f);
+        self.n_frame += 1;
+        if psf.pssn_value.is_some() {
+            self.pssn_value = psf.pssn_value;
+        }
+    }
+
+    /// Get the number of frames integrated in the long exposure
+    pub fn len(&self) -> usize {
+        self.n_frame
+    }
+
+    /// Check if no frame has been integrated yet
+    pub fn is_empty(&self) -> bool {
+        self.n_frame == 0
+    }
+
+    /// Collect the run metadata of the long exposure
+    pub fn metadata(&self) -> RunMetadata<'_> {
+        RunMetadata {
+            version: env!("CARGO_PKG_VERSION"),
+            cfd_case: self.config.cfd_case.as_deref(),
+            turbulence_effects: self.config.turbulence_effects.as_deref(),
+            wavelength_nm: self.config.wavelength_nm,
+            n_frame: self.n_frame,
+            model: self.config.model.as_ref(),
+        }
+    }
+
+    /// Save the run metadata as `run_metadata.json` into the given directory
+    ///
+    /// See [PSFs::save_metadata](crate::PSFs::save_metadata)
+    pub fn save_metadata(&self, path: impl AsRef<Path>) -> Result<(), PSFsError> {
+        self.metadata().save(path)
+    }
+
+    /// Get the long exposure PSF
+    pub fn psf(self) -> PSF {
+        let (rows, cols) = self.shape;
+        let mut psf = PSF::new(&self.config, self.frame)
+            .shape(rows, cols)
+            .long_exposure();
+        psf.optical_axis = self.optical_axis;
+        match self.pssn_value {
+            Some(value) => psf.pssn_value(value),
+            None => psf,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+/*!
+# Modulation Transfer Function
+
+This module provides the [`MtfData`] type holding the optical transfer function
+modulus of a PSF frame and its radial average.
+*/
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use image::{ImageError, Rgb, RgbImage};
+use rustfft::{FftPlanner, num_complex::Complex};
+
+/// Modulation transfer function of a PSF frame
+///
+/// The MTF is the modulus of the 2D Fourier transform of the PSF (the OTF)
+/// normalized to 1 at the zero spatial frequency.
+#[derive(Debug, Clone, Default)]
+pub struct MtfData {
+    /// 2D MTF with the zero spatial frequency at the center, row-major
+    pub mtf_2d: Vec<f64>,
+    /// (rows,columns) shape of the 2D MTF
+    pub shape: (usize, usize),
+    /// Spatial frequencies of the radial MTF
+    pub frequency: Vec<f64>,
+    /// Radially averaged MTF
+    pub mtf: Vec<f64>,
+    /// Spatial frequency unit: cycles/arcsec if the pixel scale is known, cycles/pixel otherwise
+    pub unit: &'static str,
+}
+
+impl MtfData {
+    /// Computes the MTF of a `rows`×`cols` frame
+    ///
+    /// The radial MTF is averaged over rings one frequency sample wide up to the
+    /// Nyquist frequency of the smallest dimension of the frame
+    pub(crate) fn new(
+        frame: &[f32],
+        (rows, cols): (usize, usize),
+        pixel_scale_mas: Option<f64>,
+    ) -> Self {
+        let mut otf: Vec<Complex<f64>> =
+            frame.iter().map(|&x| Complex::new(x as f64, 0.)).collect();
+        let mut planner = FftPlanner::<f64>::new();
+        let fft = planner.plan_fft_forward(cols);
+        otf.chunks_mut(cols).for_each(|row| fft.process(row));
+        let fft = planner.plan_fft_forward(rows);
+        let mut column = vec![Complex::default(); rows];
+        for j in 0..cols {
+            column
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = otf[i * cols + j]);
+            fft.process(&mut column);
+            column
+                .iter()
+                .enumerate()
+                .for_each(|(i, c)| otf[i * cols + j] = *c);
+        }
+
+        let dc = otf[0].norm();
+        let norm = if dc > 0. { dc } else { 1. };
+        let (unit, scale) = match pixel_scale_mas {
+            Some(px) => ("cycles/arcsec", 1e3 / px),
+            None => ("cycles/pixel", 1.),
+        };
+        let n = rows.max(cols) as f64;
+        let n_ring = rows.min(cols) / 2 + 1;
+        let mut sums = vec![0f64; n_ring];
+        let mut counts = vec![0usize; n_ring];
+        let mut mtf_2d = vec![0f64; rows * cols];
+        // signed frequency index of the FFT sample
+        let signed = |k: usize, n: usize| {
+            if k < n.div_ceil(2) {
+                k as f64
+            } else {
+                k as f64 - n as f64
+            }
+        };
+        for i in 0..rows {
+            for j in 0..cols {
+                let value = otf[i * cols + j].norm() / norm;
+                let (fy, fx) = (signed(i, rows) / rows as f64, signed(j, cols) / cols as f64);
+                let ring = (fx.hypot(fy) * n).round() as usize;
+                if ring < n_ring {
+                    sums[ring] += value;
+                    counts[ring] += 1;
+                }
+                mtf_2d[((i + rows / 2) % rows) * cols + (j + cols / 2) % cols] = value;
+            }
+        }
+        let (frequency, mtf) = sums
+            .into_iter()
+            .zip(counts)
+            .enumerate()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(ring, (sum, count))| (ring as f64 / n * scale, sum / count as f64))
+            .unzip();
+        Self {
+            mtf_2d,
+            shape: (rows, cols),
+            frequency,
+            mtf,
+            unit,
+        }
+    }
+
+    /// Saves the radial MTF to a CSV file with the spatial frequency and MTF columns
+    pub fn save_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "frequency [{}],mtf", self.unit)?;
+        for (f, mtf) in self.frequency.iter().zip(&self.mtf) {
+            writeln!(file, "{f:e},{mtf:e}")?;
+        }
+        file.flush()
+    }
+
+    /// Saves the 2D MTF to a PNG image with the CUBEHELIX colormap
+    ///
+    /// The zero spatial frequency is at the center of the image
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), ImageError> {
+        let (rows, cols) = self.shape;
+        RgbImage::from_fn(cols as u32, rows as u32, |x, y| {
+            let value = self.mtf_2d[y as usize * cols + x as usize].clamp(0., 1.);
+            let color = colorous::CUBEHELIX.eval_continuous(value);
+            Rgb([color.r, color.g, color.b])
+        })
+        .save(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mtf_of_a_point_source_is_flat() {
+        let mut frame = vec![0f32; 16 * 16];
+        frame[8 * 16 + 8] = 1.;
+        let mtf = MtfData::new(&frame, (16, 16), Some(10.));
+        assert_eq!(mtf.unit, "cycles/arcsec");
+        assert!(mtf.mtf.iter().all(|x| (x - 1.).abs() < 1e-9));
+        assert!((mtf.frequency[1] - 100. / 16.).abs() < 1e-9);
+    }
+}
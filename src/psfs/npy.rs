@@ -0,0 +1,54 @@
+/*!
+# NPY writer
+
+Minimal writer of the PSF frames as little-endian single precision NumPy arrays
+(format version 1.0): either a 2D array of a single frame or a 3D array of all
+the frames stacked along the 1st axis.
+*/
+
+use std::io::{self, Write};
+
+/// NPY magic string
+const MAGIC: &[u8] = b"\x93NUMPY";
+/// Alignment in bytes of the NPY preamble and header
+const ALIGN: usize = 64;
+
+/// Writes a single precision NumPy array of the given shape in C order
+///
+/// The frames are written one after the other, the product of `shape`
+/// must match the total number of values
+pub(crate) fn write_array<'a, W: Write>(
+    mut writer: W,
+    shape: &[usize],
+    frames: impl Iterator<Item = &'a [f32]>,
+) -> io::Result<()> {
+    let shape = match shape {
+        [n] => format!("({n},)"),
+        _ => format!(
+            "({})",
+            shape
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut header = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': {}, }}",
+        shape
+    );
+    // magic + version + header length + header + newline
+    let preamble = MAGIC.len() + 2 + 2;
+    let padding = (ALIGN - (preamble + header.len() + 1) % ALIGN) % ALIGN;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[1, 0])?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for frame in frames {
+        let data: Vec<u8> = frame.iter().flat_map(|x| x.to_le_bytes()).collect();
+        writer.write_all(&data)?;
+    }
+    writer.flush()
+}
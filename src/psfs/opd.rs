@@ -0,0 +1,115 @@
+/// Number of Zernike polynomials supported by [OpdStats]
+pub const N_ZERNIKE: usize = 6;
+
+/// Statistics of an OPD map over the pupil
+///
+/// The pupil is the set of non-zero finite samples of the OPD map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpdStats {
+    /// RMS of the OPD
+    pub rms: f64,
+    /// Peak-to-valley of the OPD
+    pub pv: f64,
+    /// Zernike coefficients, in Noll's ordering and RMS normalized, fitted to the OPD
+    pub zernike: Vec<f64>,
+    /// Number of samples in the pupil
+    pub valid_pixels: usize,
+}
+impl OpdStats {
+    /// Computes the statistics of a square OPD map with the pupil inscribed in the map,
+    /// fitting at most [N_ZERNIKE] Zernike polynomials
+    ///
+    /// All the values are given in the units of the OPD map.
+    /// Returns `None` if the map has no pupil sample
+    pub fn new(opd: &[f32], n_mode: usize) -> Option<Self> {
+        Some(Self {
+            rms: opd_rms(opd)?,
+            pv: opd_pv(opd)?,
+            zernike: zernike_coefficients(opd, n_mode).unwrap_or_default(),
+            valid_pixels: pupil(opd).count(),
+        })
+    }
+}
+
+fn pupil(opd: &[f32]) -> impl Iterator<Item = f32> + '_ {
+    opd.iter().copied().filter(|x| x.is_finite() && *x != 0.)
+}
+
+/// RMS of the OPD map over the pupil
+pub(crate) fn opd_rms(opd: &[f32]) -> Option<f64> {
+    let (n, s, s2) = pupil(opd)
+        .map(|x| x as f64)
+        .fold((0usize, 0f64, 0f64), |(n, s, s2), x| {
+            (n + 1, s + x, s2 + x * x)
+        });
+    (n > 0).then(|| {
+        let mean = s / n as f64;
+        (s2 / n as f64 - mean * mean).max(0.).sqrt()
+    })
+}
+
+/// Peak-to-valley of the OPD map over the pupil
+fn opd_pv(opd: &[f32]) -> Option<f64> {
+    let (min, max) = pupil(opd).fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| {
+        (min.min(x), max.max(x))
+    });
+    (min <= max).then_some((max - min) as f64)
+}
+
+/// Zernike polynomial in Noll's ordering (j=1..=6), RMS normalized over the unit circle,
+/// evaluated at the polar coordinates (r,θ)
+fn zernike(j: usize, r: f64, o: f64) -> f64 {
+    match j {
+        1 => 1.,
+        2 => 2. * r * o.cos(),
+        3 => 2. * r * o.sin(),
+        4 => 3f64.sqrt() * (2. * r * r - 1.),
+        5 => 6f64.sqrt() * r * r * (2. * o).sin(),
+        6 => 6f64.sqrt() * r * r * (2. * o).cos(),
+        _ => unreachable!("Zernike polynomial #{j} is not supported"),
+    }
+}
+
+/// Least-square fit of the first `n_mode` Zernike polynomials (Noll's ordering) to an OPD map
+fn zernike_coefficients(opd: &[f32], n_mode: usize) -> Option<Vec<f64>> {
+    let n_mode = n_mode.min(N_ZERNIKE);
+    let n = (opd.len() as f64).sqrt() as usize;
+    if n_mode == 0 || n < 2 || n * n != opd.len() {
+        return None;
+    }
+    let half = (n - 1) as f64 / 2.;
+    // normal equations: (ZᵀZ)c = Zᵀw
+    let mut a = vec![vec![0f64; n_mode + 1]; n_mode];
+    for (k, &w) in opd.iter().enumerate() {
+        if !w.is_finite() || w == 0. {
+            continue;
+        }
+        let (x, y) = ((k % n) as f64 / half - 1., (k / n) as f64 / half - 1.);
+        let (r, o) = (x.hypot(y), y.atan2(x));
+        let z: Vec<f64> = (1..=n_mode).map(|j| zernike(j, r, o)).collect();
+        for (row, zi) in a.iter_mut().zip(&z) {
+            for (aij, zj) in row.iter_mut().zip(&z) {
+                *aij += zi * zj;
+            }
+            row[n_mode] += zi * w as f64;
+        }
+    }
+    // Gauss-Jordan elimination with partial pivoting
+    for i in 0..n_mode {
+        let p = (i..n_mode).max_by(|&p, &q| a[p][i].abs().total_cmp(&a[q][i].abs()))?;
+        if a[p][i].abs() < f64::EPSILON {
+            return None;
+        }
+        a.swap(i, p);
+        let pivot = a[i].clone();
+        for (k, row) in a.iter_mut().enumerate() {
+            if k != i {
+                let f = row[i] / pivot[i];
+                for (x, p) in row.iter_mut().zip(&pivot).skip(i) {
+                    *x -= f * p;
+                }
+            }
+        }
+    }
+    Some((0..n_mode).map(|i| a[i][n_mode] / a[i][i]).collect())
+}
@@ -6,7 +6,7 @@ frames with associated metadata and rendering capabilities.
 
 ## Features
 
-- CUBEHELIX colormap visualization
+- CUBEHELIX (or [Colormap](crate::Colormap)) colormap visualization
 - Seeing and diffraction limit circle overlays
 - PSSN and metadata text overlays
 - Flexible normalization (global or local)
@@ -14,16 +14,21 @@ frames with associated metadata and rendering capabilities.
 */
 
 use std::{
+    fs,
+    io::{self, Cursor},
     iter,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
-use image::{ImageBuffer, ImageError, Rgb};
+use image::{ImageBuffer, ImageError, ImageFormat, Rgb, RgbImage};
 use imageproc::drawing::draw_hollow_circle_mut;
 
-use super::find_global_extrema;
-use crate::{Config, DETECTOR_SIZE, config::ConfigError};
+use super::{
+    MtfData, find_global_extrema, fits, npy,
+    opd::{OpdStats, opd_rms},
+};
+use crate::{Config, Enclosure, config::ConfigError};
 
 /// Errors that can occur during PSF operations
 #[derive(Debug, thiserror::Error)]
@@ -37,12 +42,60 @@ pub enum PSFError {
     /// Failed to save PSF image to file
     #[error("Failed to save PSD to png file {1:?}")]
     Save(#[source] ImageError, PathBuf),
+    /// Failed to save the SVG overlay
+    #[error("Failed to save SVG overlay to {1:?}")]
+    SaveSvg(#[source] io::Error, PathBuf),
+    /// Failed to write the PSF frame to a FITS file
+    #[error("Failed to save PSF to FITS file {1:?}")]
+    SaveFits(#[source] io::Error, PathBuf),
+    /// Failed to write the PSF frame to a NumPy file
+    #[error("Failed to save PSF to NumPy file {1:?}")]
+    SaveNpy(#[source] io::Error, PathBuf),
+    /// Failed to encode PSF image to PNG in memory
+    #[error("Failed to encode PSF to png")]
+    Encode(#[source] ImageError),
+    /// Encircled energy fraction outside (0,1]
+    #[error("Expected an encircled energy fraction in (0,1], found {0}")]
+    EnergyFraction(f64),
+    /// The frame has no flux
+    #[error("The PSF frame is empty")]
+    EmptyFrame,
+    /// The pixel scale is not known
+    #[error("The pixel scale is unknown, the optical model settings are missing")]
+    PixelScale,
     /// Configuration error during text overlay rendering
     #[error("Failed to invoke config")]
     Config(#[from] ConfigError),
 }
 type Result<T> = std::result::Result<T, PSFError>;
 
+/// Pair histogram counts with the centers of their bins evenly spread over `(min,max)`
+pub(crate) fn histogram_bins(counts: Vec<u64>, (min, max): (f32, f32)) -> Vec<(f32, u64)> {
+    let width = (max - min) / counts.len() as f32;
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| (min + (i as f32 + 0.5) * width, count))
+        .collect()
+}
+
+/// Map OPD values to RGB pixels with the OPD colormap of the configuration normalized to `(min_val,max_val)`
+pub(crate) fn opd_to_rgb(opd: &[f32], min_val: f32, max_val: f32, config: &Config) -> Vec<u8> {
+    let range = max_val - min_val;
+    let normalized: Vec<f64> = if range > 0.0 {
+        opd.iter()
+            .map(|&x| ((x - min_val) / range) as f64)
+            .collect()
+    } else {
+        vec![0.5f64; opd.len()]
+    };
+
+    normalized
+        .iter()
+        .flat_map(|&value| config.opd_rgb(value))
+        .collect()
+}
+
 /// Individual PSF frame with intensity data and associated metadata
 ///
 /// Represents a single Point Spread Function with optional PSSN value,
@@ -50,7 +103,7 @@ type Result<T> = std::result::Result<T, PSFError>;
 ///
 /// # Example
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 /// use psf::{Config, PSF};
 ///
 /// let config = Config::new(50.0, 25.0, 500.0);
@@ -63,30 +116,82 @@ type Result<T> = std::result::Result<T, PSFError>;
 #[derive(Debug, Default)]
 pub struct PSF {
     pub(crate) frame: Vec<f32>,
+    pub(crate) shape: (usize, usize),
     pub(crate) pssn_value: Option<f64>,
+    pub(crate) strehl_value: Option<f64>,
     pub(crate) frame_number: Option<usize>,
     pub(crate) opd: Option<Vec<f32>>,
+    pub(crate) n_bad_pixels: usize,
+    pub(crate) long_exposure: bool,
+    pub(crate) optical_axis: Option<(i32, i32)>,
+    pub(crate) enclosure: Option<Enclosure>,
+    pub(crate) signed: bool,
     pub(crate) config: Arc<Config>,
 }
 impl PSF {
     /// Create a new PSF frame with intensity data and shared configuration
     ///
+    /// The frame is assumed to be square, use [PSF::shape] otherwise.
+    /// Non-finite pixels (NaN or infinite values) are replaced with zero and
+    /// a warning is logged with their count, as a single bad pixel would
+    /// otherwise spoil the global normalization of a whole batch of frames.
+    ///
     /// # Parameters
     ///
     /// - `config` - Shared rendering configuration
-    /// - `frame` - PSF intensity data as flat vector (DETECTOR_SIZE²)
+    /// - `frame` - PSF intensity data as flat vector (rows × columns)
     ///
     /// # Returns
     ///
     /// PSF instance ready for metadata assignment and rendering
-    pub fn new(config: &Arc<Config>, frame: Vec<f32>) -> Self {
+    pub fn new(config: &Arc<Config>, mut frame: Vec<f32>) -> Self {
+        let n_bad_pixels = frame
+            .iter_mut()
+            .filter(|x| !x.is_finite())
+            .map(|x| *x = 0f32)
+            .count();
+        if n_bad_pixels > 0 {
+            log::warn!(
+                "PSF frame has {} non-finite pixels, they have been set to zero",
+                n_bad_pixels
+            );
+        }
+        let n = (frame.len() as f64).sqrt() as usize;
         Self {
             frame,
+            shape: (n, n),
+            n_bad_pixels,
             config: config.clone(),
             ..Default::default()
         }
     }
 
+    /// Set the (rows,columns) shape of the frame
+    ///
+    /// # Panics
+    ///
+    /// If the number of pixels does not match the frame size
+    pub fn shape(mut self, rows: usize, cols: usize) -> Self {
+        assert_eq!(
+            rows * cols,
+            self.frame.len(),
+            "PSF shape {rows}x{cols} does not match the frame size {}",
+            self.frame.len()
+        );
+        self.shape = (rows, cols);
+        self
+    }
+
+    /// Get the (rows,columns) shape of the frame
+    pub fn get_shape(&self) -> (usize, usize) {
+        self.shape
+    }
+
+    /// Get the number of non-finite pixels that have been zeroed in the frame
+    pub fn n_bad_pixels(&self) -> usize {
+        self.n_bad_pixels
+    }
+
     /// Assign PSSN (Normalized point source sensitivity) value to this frame
     ///
     /// # Parameters
@@ -101,11 +206,41 @@ impl PSF {
         self
     }
 
+    /// Get the PSSN value of this frame, if any
+    pub fn pssn(&self) -> Option<f64> {
+        self.pssn_value
+    }
+
+    /// Assign the Strehl ratio to this frame
+    ///
+    /// # Parameters
+    ///
+    /// - `value` - Strehl ratio relative to the diffraction limited reference PSF
+    ///
+    /// # Returns
+    ///
+    /// PSF instance with Strehl ratio metadata for text overlay rendering
+    pub fn strehl_value(mut self, value: f64) -> Self {
+        self.strehl_value = Some(value);
+        self
+    }
+
+    /// Get the Strehl ratio of this frame, if any
+    pub fn strehl(&self) -> Option<f64> {
+        self.strehl_value
+    }
+
     pub fn opd(mut self, opd: &[f32]) -> Self {
         self.opd = Some(opd.to_vec());
         self
     }
 
+    /// Drop the OPD map of this frame, if any
+    pub fn without_opd(mut self) -> Self {
+        self.opd = None;
+        self
+    }
+
     /// Assign frame number for animated sequence identification
     ///
     /// # Parameters
@@ -119,10 +254,308 @@ impl PSF {
         self.frame_number = Some(value);
         self
     }
-    /// Convert PSF intensity data to RGB image data using CUBEHELIX colormap
+    /// Flag the PSF as a long exposure PSF
+    ///
+    /// This selects whether the circle overlays are drawn according to [Config::overlay_frames]
+    pub fn long_exposure(mut self) -> Self {
+        self.long_exposure = true;
+        self
+    }
+    /// Set the (x,y) pixel coordinates of the optical axis in the frame
+    ///
+    /// The circle overlays are centered on the optical axis,
+    /// it defaults to the center of the frame
+    pub fn optical_axis(mut self, x: i32, y: i32) -> Self {
+        self.optical_axis = Some((x, y));
+        self
+    }
+    /// Tag the frame with the enclosure configuration of its CFD case
+    ///
+    /// The rendered frame is marked with a colored badge of the enclosure configuration
+    /// in the bottom right corner
+    pub fn enclosure(mut self, value: Enclosure) -> Self {
+        self.enclosure = Some(value);
+        self
+    }
+    /// Returns the enclosure configuration the frame is tagged with, if any
+    pub fn get_enclosure(&self) -> Option<Enclosure> {
+        self.enclosure
+    }
+    /// Returns true if this is a long exposure PSF
+    pub fn is_long_exposure(&self) -> bool {
+        self.long_exposure
+    }
+    /// Compute the intensity weighted centroid of the frame
+    ///
+    /// The minimum of the frame is subtracted from the intensity before weighting
+    /// so the background does not pull the centroid toward the center of the frame.
+    ///
+    /// # Returns
+    ///
+    /// The (x,y) centroid in pixels from the center of the frame
+    /// or `None` if the frame is empty or flat
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let (rows, cols) = self.shape;
+        let background = self.frame.iter().cloned().fold(f32::INFINITY, f32::min) as f64;
+        let (mut sx, mut sy, mut s) = (0f64, 0f64, 0f64);
+        for (k, &value) in self.frame.iter().enumerate() {
+            let value = value as f64 - background;
+            sx += value * (k % cols) as f64;
+            sy += value * (k / cols) as f64;
+            s += value;
+        }
+        (s > 0.).then(|| (sx / s - cols as f64 / 2., sy / s - rows as f64 / 2.))
+    }
+    /// Compute the radius of the disk centered on the centroid enclosing a fraction of the flux
+    ///
+    /// The pixels are sorted by distance to the [centroid](PSF::centroid), their flux is
+    /// accumulated and the radius is linearly interpolated where the cumulative flux
+    /// reaches `fraction` of the total flux.
+    ///
+    /// # Parameters
+    ///
+    /// - `fraction` - Encircled energy fraction in (0,1], e.g. 0.5 or 0.8
+    ///
+    /// # Returns
+    ///
+    /// The encircled energy radius in milli-arcseconds or an error if `fraction`
+    /// is outside (0,1], if the frame is empty or if the pixel scale is unknown
+    pub fn encircled_energy(&self, fraction: f64) -> Result<f64> {
+        if !(fraction > 0. && fraction <= 1.) {
+            return Err(PSFError::EnergyFraction(fraction));
+        }
+        let (cx, cy) = self.centroid().ok_or(PSFError::EmptyFrame)?;
+        let (rows, cols) = self.shape;
+        let (x0, y0) = (cx + cols as f64 / 2., cy + rows as f64 / 2.);
+        let mut pixels: Vec<(f64, f64)> = self
+            .frame
+            .iter()
+            .enumerate()
+            .map(|(k, &value)| {
+                let (x, y) = ((k % cols) as f64 - x0, (k / cols) as f64 - y0);
+                (x.hypot(y), value as f64)
+            })
+            .collect();
+        pixels.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let target = fraction * pixels.iter().map(|(_, value)| value).sum::<f64>();
+        let (mut r_prev, mut e_prev) = (0f64, 0f64);
+        let mut radius = pixels.last().map_or(0., |(r, _)| *r);
+        for (r, value) in pixels {
+            let e = e_prev + value;
+            if e >= target {
+                radius = if e > e_prev {
+                    r_prev + (target - e_prev) / (e - e_prev) * (r - r_prev)
+                } else {
+                    r
+                };
+                break;
+            }
+            (r_prev, e_prev) = (r, e);
+        }
+        self.config
+            .radius_mas(radius as f32)
+            .ok_or(PSFError::PixelScale)
+    }
+    /// Compute the azimuthally averaged radial profile of the frame
+    ///
+    /// The pixels are binned by distance to the [centroid](PSF::centroid) into `n_bins`
+    /// rings of equal width up to the farthest detector corner, and the flux of the pixels
+    /// is averaged within each ring.
+    /// The outer rings are only partially covered by the detector, their mean is taken over
+    /// the pixels within the detector and the rings without any pixel are skipped.
+    ///
+    /// # Returns
+    ///
+    /// The (radius,mean flux) of the rings, the radius of the ring center in milli-arcseconds,
+    /// or an error if the frame is empty or if the pixel scale is unknown
+    pub fn radial_profile(&self, n_bins: usize) -> Result<Vec<(f64, f64)>> {
+        let (cx, cy) = self.centroid().ok_or(PSFError::EmptyFrame)?;
+        let (rows, cols) = self.shape;
+        let (x0, y0) = (cx + cols as f64 / 2., cy + rows as f64 / 2.);
+        let r_max = [(0, 0), (cols - 1, 0), (0, rows - 1), (cols - 1, rows - 1)]
+            .into_iter()
+            .map(|(x, y)| (x as f64 - x0).hypot(y as f64 - y0))
+            .fold(0f64, f64::max);
+        if n_bins == 0 || r_max == 0. {
+            return Ok(vec![]);
+        }
+        let width = r_max / n_bins as f64;
+        let mut bins = vec![(0f64, 0usize); n_bins];
+        for (k, &value) in self.frame.iter().enumerate() {
+            let (x, y) = ((k % cols) as f64 - x0, (k / cols) as f64 - y0);
+            let bin = &mut bins[((x.hypot(y) / width) as usize).min(n_bins - 1)];
+            bin.0 += value as f64;
+            bin.1 += 1;
+        }
+        bins.into_iter()
+            .enumerate()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(i, (flux, count))| {
+                let radius = self
+                    .config
+                    .radius_mas(((i as f64 + 0.5) * width) as f32)
+                    .ok_or(PSFError::PixelScale)?;
+                Ok((radius, flux / count as f64))
+            })
+            .collect()
+    }
+    /// Compute the modulation transfer function of the frame
+    ///
+    /// The spatial frequencies are given in cycles/arcsec if the pixel scale is known
+    /// from the optical model settings, in cycles/pixel otherwise
+    pub fn mtf(&self) -> MtfData {
+        MtfData::new(
+            &self.frame,
+            self.shape,
+            self.config
+                .model
+                .as_ref()
+                .map(|model| model.pixel_scale_mas),
+        )
+    }
+    /// Returns the raw detector counts of the frame, row-major
+    pub fn as_slice(&self) -> &[f32] {
+        &self.frame
+    }
+    /// Get the maximum intensity of the frame
+    pub fn peak(&self) -> f32 {
+        self.frame.iter().cloned().fold(0f32, f32::max)
+    }
+    /// Compute the difference with another PSF, both normalized to unit flux
+    ///
+    /// The configuration, optical axis and exposure type of `self` are kept,
+    /// the PSSn value is dropped.
+    /// The difference is rendered as for [PSF::diff].
+    /// Returns `None` if the frames have different shapes or if either is empty
+    pub fn difference(&self, other: &PSF) -> Option<PSF> {
+        let normalized = |psf: &PSF| {
+            let flux = psf.frame.iter().sum::<f32>();
+            (flux > 0.).then(|| PSF {
+                frame: psf.frame.iter().map(|x| x / flux).collect(),
+                shape: psf.shape,
+                long_exposure: psf.long_exposure,
+                optical_axis: psf.optical_axis,
+                config: psf.config.clone(),
+                ..Default::default()
+            })
+        };
+        normalized(self)?.diff(&normalized(other)?)
+    }
+    /// Compute the pixel-wise difference `self - other` of the raw frames
+    ///
+    /// The configuration, optical axis and exposure type of `self` are kept,
+    /// the PSSn value is dropped.
+    /// The difference is rendered with a diverging colormap over a range symmetric about zero,
+    /// see [PSF::difference] for the difference of the PSFs normalized to unit flux.
+    /// Returns `None` if the frames have different shapes
+    pub fn diff(&self, other: &PSF) -> Option<PSF> {
+        if self.shape != other.shape {
+            return None;
+        }
+        let frame = self
+            .frame
+            .iter()
+            .zip(&other.frame)
+            .map(|(a, b)| a - b)
+            .collect();
+        Some(PSF {
+            frame,
+            shape: self.shape,
+            long_exposure: self.long_exposure,
+            optical_axis: self.optical_axis,
+            signed: true,
+            config: self.config.clone(),
+            ..Default::default()
+        })
+    }
+    /// Compute the RMS of the OPD map over the pupil (non-zero finite samples)
+    ///
+    /// Returns `None` if the PSF has no OPD map
+    pub fn opd_rms(&self) -> Option<f64> {
+        opd_rms(self.opd.as_ref()?)
+    }
+    /// Compute the RMS, peak-to-valley and the first `n_mode` Zernike coefficients of the OPD map
+    ///
+    /// See [OpdStats]
+    ///
+    /// Returns `None` if the PSF has no OPD map
+    pub fn opd_stats(&self, n_mode: usize) -> Option<OpdStats> {
+        OpdStats::new(self.opd.as_ref()?, n_mode)
+    }
+    /// Bin the frame by summing blocks of `bin`×`bin` pixels
+    ///
+    /// The rows and columns that do not fill a whole block are discarded.
+    /// The configuration is rescaled with [Config::binned] and the OPD map is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// If `bin` is zero
+    pub fn bin(self, bin: usize) -> Self {
+        assert!(bin > 0, "the binning factor must be greater than zero");
+        if bin == 1 {
+            return self;
+        }
+        let (rows, cols) = self.shape;
+        let (b_rows, b_cols) = (rows / bin, cols / bin);
+        let mut frame = vec![0f32; b_rows * b_cols];
+        for i in 0..b_rows * bin {
+            for j in 0..b_cols * bin {
+                frame[(i / bin) * b_cols + j / bin] += self.frame[i * cols + j];
+            }
+        }
+        Self {
+            frame,
+            shape: (b_rows, b_cols),
+            optical_axis: self
+                .optical_axis
+                .map(|(x, y)| (x / bin as i32, y / bin as i32)),
+            config: self.config.clone().binned(bin),
+            ..self
+        }
+    }
+    /// Compute the histogram of the PSF intensity
+    ///
+    /// The bins are evenly spread over the range of the frame values.
+    ///
+    /// # Parameters
+    ///
+    /// - `n_bins` - Number of bins
+    ///
+    /// # Returns
+    ///
+    /// Vector of (bin center, count) pairs
+    pub fn histogram(&self, n_bins: usize) -> Vec<(f32, u64)> {
+        let minmax = find_global_extrema(iter::once(self.frame.as_slice()));
+        let counts = self.histogram_counts(n_bins, minmax);
+        histogram_bins(counts, minmax)
+    }
+    /// Count the frame values within `n_bins` evenly spread bins over the range `(min,max)`
+    ///
+    /// Values outside the range are ignored
+    pub(crate) fn histogram_counts(&self, n_bins: usize, (min, max): (f32, f32)) -> Vec<u64> {
+        let mut counts = vec![0u64; n_bins];
+        if n_bins == 0 {
+            return counts;
+        }
+        let width = (max - min) / n_bins as f32;
+        for &x in &self.frame {
+            if x < min || x > max {
+                continue;
+            }
+            let i = if width > 0. {
+                (((x - min) / width) as usize).min(n_bins - 1)
+            } else {
+                0
+            };
+            counts[i] += 1;
+        }
+        counts
+    }
+    /// Convert PSF intensity data to RGB image data using the PSF colormap
     ///
     /// Normalizes intensity values to 0.0-1.0 range using provided min/max bounds,
-    /// then applies scientific CUBEHELIX colormap for perceptually uniform visualization.
+    /// stretches them according to the [Normalization](crate::Normalization) of the configuration,
+    /// then applies the CUBEHELIX colormap, or the [Colormap](crate::Colormap) of the configuration.
     ///
     /// # Parameters
     ///
@@ -131,13 +564,31 @@ impl PSF {
     ///
     /// # Returns
     ///
-    /// RGB pixel data as flat byte vector (3 × DETECTOR_SIZE²)
-    fn frame_to_rgb(&self, min_val: f32, max_val: f32) -> Vec<u8> {
+    /// RGB pixel data as flat byte vector (3 × rows × columns)
+    pub(crate) fn frame_to_rgb(&self, min_val: f32, max_val: f32) -> Vec<u8> {
         let range = max_val - min_val;
+        if self.signed {
+            return self
+                .frame
+                .iter()
+                .flat_map(|&x| {
+                    let value = if range > 0.0 {
+                        ((x - min_val) / range) as f64
+                    } else {
+                        0.5
+                    };
+                    self.config.signed_rgb(value)
+                })
+                .collect();
+        }
         let normalized: Vec<f64> = if range > 0.0 {
             self.frame
                 .iter()
-                .map(|&x| ((x - min_val) / range) as f64)
+                .map(|&x| {
+                    self.config
+                        .normalization
+                        .stretch(((x - min_val) / range) as f64)
+                })
                 .collect()
         } else {
             vec![0.5f64; self.frame.len()]
@@ -145,32 +596,14 @@ impl PSF {
 
         normalized
             .iter()
-            .flat_map(|&value| {
-                let color = colorous::CUBEHELIX.eval_continuous(value);
-                [color.r, color.g, color.b]
-            })
+            .flat_map(|&value| self.config.psf_rgb(value))
             .collect()
     }
     fn opd_to_rgb(&self, min_val: f32, max_val: f32) -> Result<Vec<u8>> {
         let Some(opd) = self.opd.as_ref() else {
             return Err(PSFError::OpdMissing);
         };
-        let range = max_val - min_val;
-        let normalized: Vec<f64> = if range > 0.0 {
-            opd.iter()
-                .map(|&x| ((x - min_val) / range) as f64)
-                .collect()
-        } else {
-            vec![0.5f64; opd.len()]
-        };
-
-        Ok(normalized
-            .iter()
-            .flat_map(|&value| {
-                let color = colorous::SPECTRAL.eval_continuous(value);
-                [color.r, color.g, color.b]
-            })
-            .collect())
+        Ok(opd_to_rgb(opd, min_val, max_val, &self.config))
     }
     /// Export PSF frame as annotated PNG image with local normalization
     ///
@@ -188,6 +621,66 @@ impl PSF {
     pub fn save(&self, filename: impl AsRef<Path>) -> Result<()> {
         self.save_frame_as_png(filename, None)
     }
+    /// Save the raw PSF frame as a 2D FITS image
+    ///
+    /// The detector counts are saved without normalization as a single precision
+    /// primary HDU; the header records the wavelength (`WAVELNTH` in nm), the PSSn value
+    /// (`PSSN`), the CFD case (`CFDCASE`) and the pixel scale (`PIXSCALE` in mas), if known.
+    pub fn save_as_fits(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut keywords = vec![("WAVELNTH", fits::Value::Float(self.config.wavelength_nm))];
+        if let Some(pssn) = self.pssn_value {
+            keywords.push(("PSSN", fits::Value::Float(pssn)));
+        }
+        if let Some(cfd_case) = self.config.cfd_case.as_deref() {
+            keywords.push(("CFDCASE", fits::Value::Text(cfd_case)));
+        }
+        if let Some(model) = self.config.model.as_ref() {
+            keywords.push(("PIXSCALE", fits::Value::Float(model.pixel_scale_mas)));
+        }
+        let file = fs::File::create(path).map_err(|e| PSFError::SaveFits(e, path.to_path_buf()))?;
+        fits::write_frame(io::BufWriter::new(file), self.shape, &self.frame, &keywords)
+            .map_err(|e| PSFError::SaveFits(e, path.to_path_buf()))
+    }
+    /// Save the raw PSF frame as a 2D NumPy array
+    ///
+    /// The detector counts are saved without normalization as a little-endian single precision
+    /// array of shape (rows,columns), see [PSF::as_slice]
+    pub fn save_as_npy(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let (rows, cols) = self.shape;
+        let file = fs::File::create(path).map_err(|e| PSFError::SaveNpy(e, path.to_path_buf()))?;
+        npy::write_array(
+            io::BufWriter::new(file),
+            &[rows, cols],
+            iter::once(self.frame.as_slice()),
+        )
+        .map_err(|e| PSFError::SaveNpy(e, path.to_path_buf()))
+    }
+    /// Encode the PSF frame as an annotated PNG image in memory
+    ///
+    /// The image is identical to the one written by [PSF::save]
+    pub fn to_png_bytes(&self) -> Result<Vec<u8>> {
+        self.encode_frame_as_png(None)
+    }
+    /// Encode a copy of the PSF frame binned by `bin`×`bin` pixels as an annotated PNG image in memory
+    ///
+    /// Used to preview the frames while they are generated, the OPD map is not copied.
+    pub fn to_preview_png_bytes(&self, bin: usize) -> Result<Vec<u8>> {
+        Self {
+            frame: self.frame.clone(),
+            shape: self.shape,
+            pssn_value: self.pssn_value,
+            strehl_value: self.strehl_value,
+            frame_number: self.frame_number,
+            optical_axis: self.optical_axis,
+            enclosure: self.enclosure,
+            config: self.config.clone(),
+            ..Default::default()
+        }
+        .bin(bin)
+        .to_png_bytes()
+    }
 
     /// Export PSF frame as annotated PNG image with optional global normalization
     ///
@@ -209,60 +702,129 @@ impl PSF {
         filename: impl AsRef<Path>,
         minmax: Option<(f32, f32)>,
     ) -> Result<()> {
-        // let Self {
-        //     frame,
-        //     pssn_value,
-        //     frame_number,
-        //     config,
-        // } = self;
-        let &Config {
-            seeing_radius_pixels,
-            segment_diff_lim_radius_pixels,
-            ..
-        } = &*self.config;
-
-        let (min_val, max_val) =
-            minmax.unwrap_or_else(|| find_global_extrema(iter::once(self.frame.as_slice())));
-
-        let rgb_data = self.frame_to_rgb(min_val, max_val);
-        let mut image = ImageBuffer::<Rgb<u8>, Vec<u8>>::from_raw(
-            DETECTOR_SIZE as u32,
-            DETECTOR_SIZE as u32,
-            rgb_data,
-        )
-        .ok_or_else(|| PSFError::Image)?;
-
-        let center = (DETECTOR_SIZE as i32 / 2, DETECTOR_SIZE as i32 / 2);
+        self.render_frame(minmax)?
+            .save(&filename)
+            .map_err(|e| PSFError::Save(e, filename.as_ref().to_path_buf()))?;
+        Ok(())
+    }
+    /// Render the PSF frame as an annotated RGB image
+    ///
+    /// This is the image written by [PSF::save_frame_as_png]
+    pub(crate) fn render_frame(&self, minmax: Option<(f32, f32)>) -> Result<RgbImage> {
+        let minmax = self.extrema(minmax);
+        let mut image = self.render_raw_frame(Some(minmax))?;
+        let (rows, cols) = self.shape;
 
-        // Draw seeing circle (hollow) if radius is provided
-        let white = Rgb([255u8, 255u8, 255u8]);
-        draw_hollow_circle_mut(&mut image, center, seeing_radius_pixels as i32, white);
+        let center = self
+            .optical_axis
+            .unwrap_or((cols as i32 / 2, rows as i32 / 2));
 
-        // Draw GMT segment diffraction limit circle (hollow) if radius is provided
-        draw_hollow_circle_mut(
-            &mut image,
-            center,
-            segment_diff_lim_radius_pixels as i32,
-            white,
-        );
+        // Draw the seeing and GMT segment diffraction limit circles (hollow)
+        if self.config.overlay_frames.draws(self.long_exposure) {
+            for overlay in &self.config.overlays {
+                draw_hollow_circle_mut(
+                    &mut image,
+                    center,
+                    overlay.radius_pixels as i32,
+                    overlay.color,
+                );
+            }
+            self.config.draw_legend(&mut image)?;
+        }
+        self.config.draw_wind_arrow(&mut image)?;
+        if let Some(enclosure) = self.enclosure {
+            self.config.draw_enclosure_badge(&mut image, enclosure)?;
+        }
 
         // Draw PSSN text if values are provided
         if let Some(pssn) = self.pssn_value {
             self.config
-                .draw_pssn_text(&mut image, pssn, self.frame_number)?;
+                .draw_pssn_text(&mut image, pssn, self.strehl_value, self.frame_number)?;
         }
 
-        image
-            .save(&filename)
-            .map_err(|e| PSFError::Save(e, filename.as_ref().to_path_buf()))?;
+        if self.signed {
+            Ok(self.config.append_signed_colorbar(image, minmax)?)
+        } else {
+            Ok(self.config.append_colorbar(image, minmax)?)
+        }
+    }
+    /// Returns the given or the frame intensity range, symmetric about zero for signed frames
+    fn extrema(&self, minmax: Option<(f32, f32)>) -> (f32, f32) {
+        let (min, max) =
+            minmax.unwrap_or_else(|| find_global_extrema(iter::once(self.frame.as_slice())));
+        if self.signed {
+            let max = min.abs().max(max.abs());
+            (-max, max)
+        } else {
+            (min, max)
+        }
+    }
+    /// Render the PSF frame as an RGB image without annotations
+    pub(crate) fn render_raw_frame(&self, minmax: Option<(f32, f32)>) -> Result<RgbImage> {
+        let (min_val, max_val) = self.extrema(minmax);
+
+        let rgb_data = self.frame_to_rgb(min_val, max_val);
+        let (rows, cols) = self.shape;
+        ImageBuffer::<Rgb<u8>, Vec<u8>>::from_raw(cols as u32, rows as u32, rgb_data)
+            .ok_or_else(|| PSFError::Image)
+    }
+    /// Export the PSF frame as a PNG image without annotations and an SVG overlay
+    ///
+    /// The annotations (circle overlays, labels, PSSN and metadata text) are written
+    /// to an SVG file, with the same name than the PNG file but with the `svg` extension,
+    /// that can be composited on top of the PNG image.
+    ///
+    /// # Parameters
+    ///
+    /// - `png_path` - Output PNG file path
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or rendering/save error
+    pub fn save_with_sidecar_svg(&self, png_path: impl AsRef<Path>) -> Result<()> {
+        let png_path = png_path.as_ref();
+        self.render_raw_frame(None)?
+            .save(png_path)
+            .map_err(|e| PSFError::Save(e, png_path.to_path_buf()))?;
+        let (rows, cols) = self.shape;
+        let center = self
+            .optical_axis
+            .unwrap_or((cols as i32 / 2, rows as i32 / 2));
+        let svg = self.config.svg_overlay(
+            (cols as u32, rows as u32),
+            center,
+            self.long_exposure,
+            self.pssn_value,
+            self.strehl_value,
+            self.frame_number,
+        );
+        let svg_path = png_path.with_extension("svg");
+        fs::write(&svg_path, svg).map_err(|e| PSFError::SaveSvg(e, svg_path))?;
         Ok(())
     }
+    /// Encode the PSF frame as an annotated PNG image in memory
+    pub(crate) fn encode_frame_as_png(&self, minmax: Option<(f32, f32)>) -> Result<Vec<u8>> {
+        let mut buffer = Cursor::new(Vec::new());
+        self.render_frame(minmax)?
+            .write_to(&mut buffer, ImageFormat::Png)
+            .map_err(PSFError::Encode)?;
+        Ok(buffer.into_inner())
+    }
     pub fn save_opd_as_png(
         &self,
         filename: impl AsRef<Path>,
         minmax: Option<(f32, f32)>,
     ) -> Result<()> {
-        let Some(opd) = self.opd.as_ref().map(|opd| opd.as_slice()) else {
+        self.render_opd(minmax)?
+            .save(&filename)
+            .map_err(|e| PSFError::Save(e, filename.as_ref().to_path_buf()))?;
+        Ok(())
+    }
+    /// Render the OPD map as an annotated RGB image
+    ///
+    /// This is the image written by [PSF::save_opd_as_png]
+    pub(crate) fn render_opd(&self, minmax: Option<(f32, f32)>) -> Result<RgbImage> {
+        let Some(opd) = self.opd.as_deref() else {
             return Err(PSFError::OpdMissing);
         };
 
@@ -276,9 +838,14 @@ impl PSF {
         self.config
             .draw_opd_text(&mut image, self.frame_number, Some((min_val, max_val)))?;
 
-        image
-            .save(&filename)
-            .map_err(|e| PSFError::Save(e, filename.as_ref().to_path_buf()))?;
-        Ok(())
+        Ok(image)
+    }
+    /// Encode the OPD map as an annotated PNG image in memory
+    pub(crate) fn encode_opd_as_png(&self, minmax: Option<(f32, f32)>) -> Result<Vec<u8>> {
+        let mut buffer = Cursor::new(Vec::new());
+        self.render_opd(minmax)?
+            .write_to(&mut buffer, ImageFormat::Png)
+            .map_err(PSFError::Encode)?;
+        Ok(buffer.into_inner())
     }
 }
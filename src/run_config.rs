@@ -0,0 +1,181 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{AzimuthAngle, CfdCaseKey, Enclosure, ParseCfdCaseError, WindSpeed, ZenithAngle};
+
+/// M1 & M2 rigid body motions time series of the wind loads
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RbmSeries {
+    /// Open loop segment motions
+    #[default]
+    OpenLoop,
+    /// Compensate the segment tip-tilt with the FSM
+    Fsm,
+    /// Compensate the segment piston and tip-tilt with the ASM
+    Asm,
+    /// Compensate the segment piston (updated RTF) and tip-tilt with the ASM
+    Asm2,
+}
+
+impl RbmSeries {
+    /// Returns the name of the parquet file with the RBM time series
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::OpenLoop => "m1_m2_rbms.parquet",
+            Self::Fsm => "m1_m2_rbms.FSM.parquet",
+            Self::Asm => "m1_m2_rbms.ASM.parquet",
+            Self::Asm2 => "m1_m2_rbms.ASM.2.parquet",
+        }
+    }
+}
+
+impl Display for RbmSeries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::OpenLoop => "Wind Loads",
+                Self::Fsm => "(Wind Loads - FSM)",
+                Self::Asm => "(Wind Loads - ASM)",
+                Self::Asm2 => "(Wind Loads - ASM2)",
+            }
+        )
+    }
+}
+
+/// CFD case and turbulence effects of a PSF run
+///
+/// Shared by the command line interface and the web application
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunConfig {
+    /// Dome seeing turbulence effects
+    pub domeseeing: bool,
+    /// Wind loads effects with the given RBM time series
+    pub windloads: Option<RbmSeries>,
+    pub zenith_angle: ZenithAngle,
+    pub azimuth_angle: AzimuthAngle,
+    pub wind_speed: WindSpeed,
+    /// Enclosure configuration, inferred from the wind speed and the zenith angle if `None`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub enclosure: Option<Enclosure>,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            domeseeing: false,
+            windloads: None,
+            zenith_angle: ZenithAngle::Thirty,
+            azimuth_angle: AzimuthAngle::Zero,
+            wind_speed: WindSpeed::Seven,
+            enclosure: None,
+        }
+    }
+}
+
+impl FromStr for RunConfig {
+    type Err = ParseCfdCaseError;
+
+    /// Parses the CFD case of a CFD case name, e.g. `zen30az045_CD12`, see [CfdCaseKey]
+    ///
+    /// The enclosure configuration is given by the name and the turbulence effects are not set
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseCfdCaseError(s.to_string());
+        let key: CfdCaseKey = s.parse()?;
+        let zenith_angle = ZenithAngle::all()
+            .into_iter()
+            .find(|zenith| zenith.as_u32() == 90 - key.elevation)
+            .ok_or_else(err)?;
+        let azimuth_angle = AzimuthAngle::all()
+            .into_iter()
+            .find(|azimuth| azimuth.as_u32() == key.azimuth)
+            .ok_or_else(err)?;
+        let wind_speed = WindSpeed::all()
+            .into_iter()
+            .find(|wind_speed| wind_speed.as_u32() == key.wind_speed)
+            .ok_or_else(err)?;
+        let enclosure = [
+            Enclosure::OpenSky,
+            Enclosure::ClosedDome,
+            Enclosure::ClosedSky,
+        ]
+        .into_iter()
+        .find(|enclosure| enclosure.as_str() == key.enclosure)
+        .ok_or_else(err)?;
+        Ok(Self {
+            zenith_angle,
+            azimuth_angle,
+            wind_speed,
+            enclosure: Some(enclosure),
+            ..Default::default()
+        })
+    }
+}
+
+impl RunConfig {
+    /// Returns the enclosure configuration of the CFD case
+    pub fn enclosure(&self) -> &'static str {
+        self.enclosure
+            .unwrap_or_else(|| Enclosure::new(self.wind_speed.as_u32(), self.zenith_angle))
+            .as_str()
+    }
+    /// Returns the colloquial name of the CFD case, e.g. `zen30az045_OS7`
+    pub fn colloquial(&self) -> String {
+        format!(
+            "zen{:02}az{:03}_{}{}",
+            self.zenith_angle.as_u32(),
+            self.azimuth_angle.as_u32(),
+            self.enclosure().to_uppercase(),
+            self.wind_speed.as_u32()
+        )
+    }
+    /// Returns the description of the turbulence effects
+    ///
+    /// Returns `None` if neither dome seeing nor wind loads are selected
+    pub fn turbulence_effects(&self) -> Option<String> {
+        match (self.domeseeing, self.windloads) {
+            (true, None) => Some("Dome Seeing".to_string()),
+            (true, Some(series)) => Some(format!("Dome Seeing + {series}")),
+            (false, Some(series)) => Some(series.to_string()),
+            (false, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rbm_series_file_names() {
+        assert_eq!(RbmSeries::OpenLoop.file_name(), "m1_m2_rbms.parquet");
+        assert_eq!(RbmSeries::Fsm.file_name(), "m1_m2_rbms.FSM.parquet");
+        assert_eq!(RbmSeries::Asm.file_name(), "m1_m2_rbms.ASM.parquet");
+        assert_eq!(RbmSeries::Asm2.file_name(), "m1_m2_rbms.ASM.2.parquet");
+    }
+
+    #[test]
+    fn colloquial_cfd_case() {
+        let run_config = RunConfig::default();
+        assert_eq!(run_config.colloquial(), "zen30az000_OS7");
+        let key: crate::CfdCaseKey = run_config.colloquial().parse().unwrap();
+        assert_eq!((key.elevation, key.azimuth), (60, 0));
+        assert_eq!((key.enclosure.as_str(), key.wind_speed), ("os", 7));
+    }
+
+    #[test]
+    fn cfd_case_enclosure_is_not_inferred() {
+        let run_config: RunConfig = "zen30az045_CD7".parse().unwrap();
+        assert_eq!(run_config.zenith_angle, ZenithAngle::Thirty);
+        assert_eq!(run_config.azimuth_angle, AzimuthAngle::FortyFive);
+        assert_eq!(run_config.enclosure(), "cd");
+        assert_eq!(run_config.colloquial(), "zen30az045_CD7");
+        assert!("zen45az045_CD7".parse::<RunConfig>().is_err());
+    }
+}
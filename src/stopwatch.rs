@@ -0,0 +1,92 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+/// Wall-clock time accumulated by a pipeline stage
+#[derive(Debug, Clone, Copy, Default)]
+struct Lap {
+    total: Duration,
+    count: usize,
+}
+
+/// Lightweight wall-clock instrumentation of the PSF pipeline
+///
+/// The time spent in each stage (ray tracing, detector read, PSSn, PNG encoding, ...)
+/// is accumulated across frames and summarized with [Display]:
+/// ```rust,no_run
+/// # use gmt_cfd_psf::Stopwatch;
+/// let mut stopwatch = Stopwatch::new();
+/// for _ in 0..10 {
+///     stopwatch.time("ray tracing", || { /* ... */ });
+/// }
+/// println!("{stopwatch}");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Stopwatch {
+    laps: Vec<(&'static str, Lap)>,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Runs `f` and adds its wall-clock time to the given stage
+    pub fn time<T>(&mut self, stage: &'static str, f: impl FnOnce() -> T) -> T {
+        let now = Instant::now();
+        let value = f();
+        self.add(stage, now.elapsed());
+        value
+    }
+    /// Adds a duration to the given stage
+    pub fn add(&mut self, stage: &'static str, elapsed: Duration) {
+        let lap = match self.laps.iter_mut().find(|(name, _)| *name == stage) {
+            Some((_, lap)) => lap,
+            None => {
+                self.laps.push((stage, Lap::default()));
+                &mut self.laps.last_mut().unwrap().1
+            }
+        };
+        lap.total += elapsed;
+        lap.count += 1;
+    }
+    /// Returns the time accumulated by a stage
+    pub fn total(&self, stage: &str) -> Option<Duration> {
+        self.laps
+            .iter()
+            .find(|(name, _)| *name == stage)
+            .map(|(_, lap)| lap.total)
+    }
+    /// Returns the time accumulated by all the stages
+    pub fn grand_total(&self) -> Duration {
+        self.laps.iter().map(|(_, lap)| lap.total).sum()
+    }
+}
+
+impl Display for Stopwatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let grand_total = self.grand_total().as_secs_f64();
+        writeln!(
+            f,
+            "{:<16} {:>10} {:>6} {:>10} {:>6}",
+            "stage", "total [s]", "count", "mean [ms]", "%"
+        )?;
+        for (name, lap) in &self.laps {
+            let total = lap.total.as_secs_f64();
+            writeln!(
+                f,
+                "{:<16} {:>10.3} {:>6} {:>10.3} {:>6.1}",
+                name,
+                total,
+                lap.count,
+                1e3 * total / lap.count as f64,
+                if grand_total > 0. {
+                    100. * total / grand_total
+                } else {
+                    0.
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
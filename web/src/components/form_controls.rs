@@ -1,81 +1,26 @@
-use std::{collections::HashMap, fmt::Display};
+use std::collections::HashMap;
 
 use leptos::prelude::Show;
 use leptos::prelude::*;
-use psf::{get_enclosure_config, AzimuthAngle, ElevationAngle, WindSpeed, ZenithAngle};
-use serde::{Deserialize, Serialize};
+use psf::{
+    cfd_case_title, get_enclosure_config, AzimuthAngle, ElevationAngle, RbmSeries, RunConfig,
+    WindSpeed, ZenithAngle,
+};
 
 use crate::components::youtube_playlists;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum RbmTimeSeries {
-    OpenLoop,
-    Fsm,
-    Asm,
-}
-impl RbmTimeSeries {
-    pub fn file_name(&self) -> &str {
-        match self {
-            Self::OpenLoop => "m1_m2_rbms.parquet",
-            Self::Fsm => "m1_m2_rbms.FSM.parquet",
-            Self::Asm => "m1_m2_rbms.ASM.2.parquet",
-        }
-    }
-}
-impl Display for RbmTimeSeries {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::OpenLoop => "Wind Loads",
-                Self::Fsm => "(Wind Loads - FSM)",
-                Self::Asm => "(Wind Loads - ASM)",
-            }
-        )
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct PsfConfig {
-    pub domeseeing: bool,
-    pub windloads: bool,
-    pub elevation_angle: ElevationAngle,
-    pub azimuth_angle: AzimuthAngle,
-    pub wind_speed: WindSpeed,
-    pub rbm_time_series: RbmTimeSeries,
-}
-
-impl Default for PsfConfig {
-    fn default() -> Self {
-        Self {
-            domeseeing: false,
-            windloads: false,
-            elevation_angle: ElevationAngle::Sixty,
-            azimuth_angle: AzimuthAngle::Zero,
-            wind_speed: WindSpeed::Seven,
-            rbm_time_series: RbmTimeSeries::OpenLoop,
-        }
-    }
-}
-
 #[component]
-pub fn CfdData(config: RwSignal<PsfConfig>) -> impl IntoView {
+pub fn CfdData(config: RwSignal<RunConfig>) -> impl IntoView {
+    // RBM time series selected in the form, kept while the wind loads are unchecked
+    let rbm_series = RwSignal::new(config.get_untracked().windloads.unwrap_or_default());
+
     // Function to generate YouTube video title based on configuration
     let domeseeing_playlist: HashMap<String, String> =
         serde_json::from_str(youtube_playlists::DOMESEEING).unwrap();
     let (domeseeing_playlist, ..) = signal(domeseeing_playlist);
     let get_domeseeing_video = move || {
         let cfg = config.get();
-        let zenith_str = format!("{:02}", ZenithAngle::from(cfg.elevation_angle).as_u32());
-        let azimuth_str = format!("{:03}", cfg.azimuth_angle.as_u32());
-        let enclosure =
-            get_enclosure_config(cfg.wind_speed.as_u32(), cfg.elevation_angle).to_uppercase();
-        let wind_speed = cfg.wind_speed.as_u32();
-        let title = format!(
-            "zen{}az{}_{}_{wind_speed}ms",
-            zenith_str, azimuth_str, enclosure
-        );
+        let title = cfd_case_title(cfg.zenith_angle.into(), cfg.azimuth_angle, cfg.wind_speed);
         let id = domeseeing_playlist.get().get(&title).unwrap().to_owned();
         (title, id)
     };
@@ -85,15 +30,7 @@ pub fn CfdData(config: RwSignal<PsfConfig>) -> impl IntoView {
     let (windloads_playlist, ..) = signal(windloads_playlist);
     let get_windloads_video = move || {
         let cfg = config.get();
-        let zenith_str = format!("{:02}", ZenithAngle::from(cfg.elevation_angle).as_u32());
-        let azimuth_str = format!("{:03}", cfg.azimuth_angle.as_u32());
-        let enclosure =
-            get_enclosure_config(cfg.wind_speed.as_u32(), cfg.elevation_angle).to_uppercase();
-        let wind_speed = cfg.wind_speed.as_u32();
-        let title = format!(
-            "zen{}az{}_{}_{wind_speed}ms",
-            zenith_str, azimuth_str, enclosure
-        );
+        let title = cfd_case_title(cfg.zenith_angle.into(), cfg.azimuth_angle, cfg.wind_speed);
         let id = windloads_playlist.get().get(&title).unwrap().to_owned();
         (title, id)
     };
@@ -126,10 +63,10 @@ pub fn CfdData(config: RwSignal<PsfConfig>) -> impl IntoView {
                             <label class="flex items-center space-x-2">
                                 <input
                                     type="checkbox"
-                                    checked=move || config.get().windloads
+                                    checked=move || config.get().windloads.is_some()
                                     on:change=move |ev| {
                                         let checked = event_target_checked(&ev);
-                                        config.update(|c| c.windloads = checked);
+                                        config.update(|c| c.windloads = checked.then(|| rbm_series.get_untracked()));
                                     }
                                     class="w-4 h-4 text-blue-600 bg-gray-100 border-gray-300 rounded focus:ring-blue-500"
                                 />
@@ -140,30 +77,33 @@ pub fn CfdData(config: RwSignal<PsfConfig>) -> impl IntoView {
                                 class="p-1 border border-gray-300 rounded-md focus:ring-blue-500 focus:border-blue-500 text-sm"
                                 on:change=move |ev| {
                                     let value = event_target_value(&ev);
-                                    let rbm_series = match value.as_str() {
-                                        "OpenLoop" => RbmTimeSeries::OpenLoop,
-                                        "Fsm" => RbmTimeSeries::Fsm,
-                                        "Asm" => RbmTimeSeries::Asm,
-                                        _ => RbmTimeSeries::OpenLoop,
+                                    let series = match value.as_str() {
+                                        "OpenLoop" => RbmSeries::OpenLoop,
+                                        "Fsm" => RbmSeries::Fsm,
+                                        "Asm" => RbmSeries::Asm2,
+                                        _ => RbmSeries::OpenLoop,
                                     };
-                                    config.update(|c| c.rbm_time_series = rbm_series);
+                                    rbm_series.set(series);
+                                    config.update(|c| if c.windloads.is_some() {
+                                        c.windloads = Some(series);
+                                    });
                                 }
                             >
                                 <option
                                     value="OpenLoop"
-                                    selected=move || matches!(config.get().rbm_time_series, RbmTimeSeries::OpenLoop)
+                                    selected=move || rbm_series.get() == RbmSeries::OpenLoop
                                 >
                                     "open-loop"
                                 </option>
                                 <option
                                     value="Fsm"
-                                    selected=move || matches!(config.get().rbm_time_series, RbmTimeSeries::Fsm)
+                                    selected=move || rbm_series.get() == RbmSeries::Fsm
                                 >
                                     "closed-loop FSM"
                                 </option>
                                 <option
                                     value="Asm"
-                                    selected=move || matches!(config.get().rbm_time_series, RbmTimeSeries::Asm)
+                                    selected=move || rbm_series.get() == RbmSeries::Asm2
                                 >
                                     "closed-loop ASM"
                                 </option>
@@ -172,7 +112,7 @@ pub fn CfdData(config: RwSignal<PsfConfig>) -> impl IntoView {
                     </div>
 
                     // YouTube videos section - side by side layout
-                    <Show when=move || config.get().domeseeing || config.get().windloads>
+                    <Show when=move || config.get().turbulence_effects().is_some()>
                         <div class="mt-4 border-t border-gray-200 pt-4">
                             <div class="grid grid-cols-1 md:grid-cols-2 gap-4">
                                 // DomeSeeing video (left side)
@@ -201,7 +141,7 @@ pub fn CfdData(config: RwSignal<PsfConfig>) -> impl IntoView {
                                 </Show>
 
                                 // Windloads video (right side)
-                                <Show when=move || config.get().windloads>
+                                <Show when=move || config.get().windloads.is_some()>
                                     {move || {
                                         let (video_title, video_id) = get_windloads_video();
                                         view! {
@@ -231,7 +171,7 @@ pub fn CfdData(config: RwSignal<PsfConfig>) -> impl IntoView {
     }
 }
 #[component]
-pub fn ElevationAngle(config: RwSignal<PsfConfig>) -> impl IntoView {
+pub fn ElevationAngle(config: RwSignal<RunConfig>) -> impl IntoView {
     let get_zenith_image = |angle: &ElevationAngle| -> &'static str {
         match angle {
             ElevationAngle::Ninety => "/assets/zen00az000_OS7_tel_tr.png",
@@ -247,8 +187,8 @@ pub fn ElevationAngle(config: RwSignal<PsfConfig>) -> impl IntoView {
                             </label>
                             <div class="mt-2">
                                 <img
-                                    src=move || get_zenith_image(&config.get().elevation_angle)
-                                    alt=move || format!("Zenith angle {} illustration", config.get().elevation_angle.as_str())
+                                    src=move || get_zenith_image(&ElevationAngle::from(config.get().zenith_angle))
+                                    alt=move || format!("Zenith angle {} illustration", ElevationAngle::from(config.get().zenith_angle).as_str())
                                     class="h-auto rounded border border-gray-200"
                                     style="width: 55%"
                                 />
@@ -263,11 +203,11 @@ pub fn ElevationAngle(config: RwSignal<PsfConfig>) -> impl IntoView {
                                         "30" => ElevationAngle::Thirty,
                                         _ => ElevationAngle::Thirty,
                                     };
-                                    config.update(|c| c.elevation_angle = zenith);
+                                    config.update(|c| c.zenith_angle = zenith.into());
                                 }
                             >
                                 {ElevationAngle::all().into_iter().map(|angle| {
-                                    let selected = move || config.get().elevation_angle == angle;
+                                    let selected = move || ElevationAngle::from(config.get().zenith_angle) == angle;
                                     view! {
                                         <option
                                             value={angle.as_u32().to_string()}
@@ -284,7 +224,7 @@ pub fn ElevationAngle(config: RwSignal<PsfConfig>) -> impl IntoView {
 }
 
 #[component]
-pub fn AzimuthAngle(config: RwSignal<PsfConfig>) -> impl IntoView {
+pub fn AzimuthAngle(config: RwSignal<RunConfig>) -> impl IntoView {
     let get_azimuth_image = |angle: &AzimuthAngle| -> &'static str {
         match angle {
             AzimuthAngle::Zero => "/assets/az0.png",
@@ -339,7 +279,7 @@ pub fn AzimuthAngle(config: RwSignal<PsfConfig>) -> impl IntoView {
 }
 
 #[component]
-pub fn WindSpeed(config: RwSignal<PsfConfig>) -> impl IntoView {
+pub fn WindSpeed(config: RwSignal<RunConfig>) -> impl IntoView {
     view! {
                 <div>
                     <label class="block text-sm font-medium text-gray-700 mb-2">
@@ -349,11 +289,11 @@ pub fn WindSpeed(config: RwSignal<PsfConfig>) -> impl IntoView {
                         <img
                             src=move || {
                                 let cfg = config.get();
-                                get_enclosure_image(cfg.wind_speed.as_u32(), cfg.elevation_angle)
+                                get_enclosure_image(cfg.wind_speed.as_u32(), cfg.zenith_angle)
                             }
                             alt=move || {
                                 let cfg = config.get();
-                                let enclosure = get_enclosure_config(cfg.wind_speed.as_u32(), cfg.elevation_angle);
+                                let enclosure = get_enclosure_config(cfg.wind_speed.as_u32(), cfg.zenith_angle);
                                 format!("Enclosure configuration: {}", enclosure)
                             }
                             class="h-auto rounded border border-gray-200"
@@ -419,10 +359,10 @@ fn get_enclosure_image(wind_speed: u32, pointing: impl Into<ZenithAngle>) -> &'s
 }
 
 #[component]
-pub fn Vents(config: RwSignal<PsfConfig>) -> impl IntoView {
+pub fn Vents(config: RwSignal<RunConfig>) -> impl IntoView {
     let vents_status = move || {
         let cfg = config.get();
-        get_vents_status(cfg.wind_speed.as_u32(), cfg.elevation_angle)
+        get_vents_status(cfg.wind_speed.as_u32(), cfg.zenith_angle)
     };
 
     view! {
@@ -434,11 +374,11 @@ pub fn Vents(config: RwSignal<PsfConfig>) -> impl IntoView {
                 <img
                     src=move || {
                         let cfg = config.get();
-                        get_enclosure_image(cfg.wind_speed.as_u32(), cfg.elevation_angle)
+                        get_enclosure_image(cfg.wind_speed.as_u32(), cfg.zenith_angle)
                     }
                     alt=move || {
                         let cfg = config.get();
-                        let enclosure = get_enclosure_config(cfg.wind_speed.as_u32(), cfg.elevation_angle);
+                        let enclosure = get_enclosure_config(cfg.wind_speed.as_u32(), cfg.zenith_angle);
                         format!("Enclosure configuration: {}", enclosure)
                     }
                     class="h-auto rounded border border-gray-200"
@@ -456,10 +396,10 @@ pub fn Vents(config: RwSignal<PsfConfig>) -> impl IntoView {
 }
 
 #[component]
-pub fn WindScreen(config: RwSignal<PsfConfig>) -> impl IntoView {
+pub fn WindScreen(config: RwSignal<RunConfig>) -> impl IntoView {
     let wind_screen_status = move || {
         let cfg = config.get();
-        get_wind_screen_status(cfg.wind_speed.as_u32(), cfg.elevation_angle)
+        get_wind_screen_status(cfg.wind_speed.as_u32(), cfg.zenith_angle)
     };
 
     view! {
@@ -471,11 +411,11 @@ pub fn WindScreen(config: RwSignal<PsfConfig>) -> impl IntoView {
                 <img
                     src=move || {
                         let cfg = config.get();
-                        get_enclosure_image(cfg.wind_speed.as_u32(), cfg.elevation_angle)
+                        get_enclosure_image(cfg.wind_speed.as_u32(), cfg.zenith_angle)
                     }
                     alt=move || {
                         let cfg = config.get();
-                        let enclosure = get_enclosure_config(cfg.wind_speed.as_u32(), cfg.elevation_angle);
+                        let enclosure = get_enclosure_config(cfg.wind_speed.as_u32(), cfg.zenith_angle);
                         format!("Enclosure configuration: {}", enclosure)
                     }
                     class="h-auto rounded border border-gray-200"
@@ -493,7 +433,7 @@ pub fn WindScreen(config: RwSignal<PsfConfig>) -> impl IntoView {
 }
 
 #[component]
-pub fn ConfigForm(config: RwSignal<PsfConfig>, on_submit: impl Fn() + 'static) -> impl IntoView {
+pub fn ConfigForm(config: RwSignal<RunConfig>, on_submit: impl Fn() + 'static) -> impl IntoView {
     view! {
         <div class="bg-white rounded-lg shadow-md p-6">
             <h2 class="text-2xl font-semibold mb-4 text-gray-800">
@@ -3,15 +3,25 @@ use std::path::Path;
 use futures::StreamExt;
 use gloo_timers::future::IntervalStream;
 use leptos::{prelude::*, task::spawn_local};
+use psf::RunConfig;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    components::form_controls::{ConfigForm, PsfConfig},
-    server::{get_frame_id, opd_animation, psf_animation, psf_generation},
-    N_SAMPLE,
+    components::form_controls::ConfigForm,
+    server::{
+        download_fits, get_frame_id, get_n_sample, get_preview, list_sessions, opd_animation,
+        psf_animation, psf_generation,
+    },
 };
 
+/// Progress polling interval in milliseconds
+///
+/// Scales with the number of frames, 10ms per frame, within 250ms and 2s
+fn poll_interval_ms(n_frame: usize) -> u32 {
+    (n_frame as u32).saturating_mul(10).clamp(250, 2000)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenerationStatus {
     pub session_id: String,
@@ -37,9 +47,20 @@ pub struct GeneratedImage {
     pub description: String,
 }
 
+/// Outputs of a previous PSF generation session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorSession {
+    pub session_id: String,
+    /// CFD case from the run metadata
+    pub cfd_case: Option<String>,
+    /// Turbulence effects from the run metadata
+    pub turbulence_effects: Option<String>,
+    pub images: Vec<GeneratedImage>,
+}
+
 #[component]
 pub fn PsfGenerator() -> impl IntoView {
-    let config = RwSignal::new(PsfConfig::default());
+    let config = RwSignal::new(RunConfig::default());
     let generation_status = RwSignal::new(GenerationStatus {
         session_id: String::new(),
         status: ProcessingStatus::Idle,
@@ -47,13 +68,15 @@ pub fn PsfGenerator() -> impl IntoView {
         progress: None,
         images: Vec::new(),
     });
+    // PNG data URL of the latest frame while the PSF frames are generated
+    let preview = RwSignal::new(None::<String>);
 
     let generate_psf = move || {
         let config_value = config.get();
         let session_id = Uuid::new_v4().to_string();
 
         // Validate that at least one turbulence effect is selected
-        if !config_value.domeseeing && !config_value.windloads {
+        if config_value.turbulence_effects().is_none() {
             generation_status.set(GenerationStatus {
                 session_id,
                 status: ProcessingStatus::Error,
@@ -74,11 +97,14 @@ pub fn PsfGenerator() -> impl IntoView {
             images: Vec::new(),
         });
 
+        preview.set(None);
+
         // Start progress tracking timer
         let generation_status_clone = generation_status.clone();
         let session_id_clone = session_id.clone();
         spawn_local(async move {
-            let mut interval = IntervalStream::new(1000); // 1 second intervals
+            let n_sample = get_n_sample().await.unwrap_or_default();
+            let mut interval = IntervalStream::new(poll_interval_ms(n_sample));
 
             while let Some(_) = interval.next().await {
                 let current_status = generation_status_clone.get_untracked();
@@ -86,9 +112,10 @@ pub fn PsfGenerator() -> impl IntoView {
                 // Only update progress if we're still processing
                 if matches!(current_status.status, ProcessingStatus::Generating) {
                     match get_frame_id().await {
-                        Ok(frame_id) => {
-                            // Calculate progress: frame_id ranges from 0 to 99, so progress is 0-100%
-                            let progress = ((frame_id + 1) as f32 / N_SAMPLE as f32) * 100.0;
+                        Ok((frame_id, n_frame)) => {
+                            // Calculate progress: frame_id ranges from 0 to n_frame-1, so progress is 0-100%
+                            let progress =
+                                ((frame_id + 1) as f32 / n_frame.max(1) as f32).min(1.0) * 100.0;
 
                             generation_status_clone.update(|status| {
                                 if status.session_id == session_id_clone {
@@ -102,8 +129,12 @@ pub fn PsfGenerator() -> impl IntoView {
                             continue;
                         }
                     }
+                    if let Ok(Some(src)) = get_preview(session_id_clone.clone()).await {
+                        preview.set(Some(src));
+                    }
                 } else {
                     // Stop polling if no longer processing
+                    preview.set(None);
                     break;
                 }
             }
@@ -123,6 +154,8 @@ proceeding to creating short exposure PSFs animation"#
                     });
 
                     let output_dir = Path::new(&images[1].path).parent().unwrap().to_path_buf();
+                    // the frames are kept if an animation fails
+                    let mut errors = vec![];
                     match psf_animation(output_dir.clone()).await {
                         Ok(image) => {
                             images.push(image);
@@ -135,32 +168,23 @@ proceeding to creating OPDs animation"#
                                 status.progress = Some(50.0);
                             });
                         }
-                        Err(e) => generation_status.set(GenerationStatus {
-                            session_id: session_id.clone(),
-                            status: ProcessingStatus::Error,
-                            message: format!("Error creating PSFs animation: {}", e),
-                            progress: None,
-                            images: Vec::new(),
-                        }),
+                        Err(e) => errors.push(format!("Error creating PSFs animation: {}", e)),
                     }
                     match opd_animation(output_dir).await {
-                        Ok(image) => {
-                            images.push(image);
-                            generation_status.update(|status| {
-                                status.images = images;
-                                status.status = ProcessingStatus::Complete;
-                                status.message = "Generation & animation complete!".to_string();
-                                status.progress = Some(100.0);
-                            });
-                        }
-                        Err(e) => generation_status.set(GenerationStatus {
-                            session_id,
-                            status: ProcessingStatus::Error,
-                            message: format!("Error creating OPDs animation: {}", e),
-                            progress: None,
-                            images: Vec::new(),
-                        }),
+                        Ok(image) => images.push(image),
+                        Err(e) => errors.push(format!("Error creating OPDs animation: {}", e)),
                     }
+                    generation_status.update(|status| {
+                        status.images = images;
+                        status.progress = Some(100.0);
+                        if errors.is_empty() {
+                            status.status = ProcessingStatus::Complete;
+                            status.message = "Generation & animation complete!".to_string();
+                        } else {
+                            status.status = ProcessingStatus::Error;
+                            status.message = errors.join("; ");
+                        }
+                    });
                 }
                 Err(e) => generation_status.set(GenerationStatus {
                     session_id,
@@ -180,6 +204,8 @@ proceeding to creating OPDs animation"#
             <StatusDisplay generation_status=generation_status/>
 
             <ImageGallery generation_status=generation_status/>
+
+            <PriorSessions generation_status=generation_status/>
         </div>
     }
 }
@@ -214,6 +240,13 @@ fn StatusDisplay(generation_status: RwSignal<GenerationStatus>) -> impl IntoView
                                     ></div>
                                 </div>
                             })}
+                            {move || preview.get().map(|src| view! {
+                                <img
+                                    src=src
+                                    alt="Latest PSF frame"
+                                    class="w-48 h-48 rounded border border-gray-300"
+                                />
+                            })}
                         </div>
                     }.into_any(),
                     ProcessingStatus::Complete => view! {
@@ -278,9 +311,101 @@ fn ImageGallery(generation_status: RwSignal<GenerationStatus>) -> impl IntoView
                                 </div>
                             }).collect::<Vec<_>>()}
                         </div>
+                        <Show when=move || matches!(generation_status.get().status, ProcessingStatus::Complete)>
+                            <DownloadFits session_id=status.session_id.clone()/>
+                        </Show>
                     }.into_any()
                 }
             }}
         </div>
     }
 }
+
+#[component]
+fn DownloadFits(session_id: String) -> impl IntoView {
+    let message = RwSignal::new(None::<String>);
+    let download = move |_| {
+        let session_id = session_id.clone();
+        spawn_local(async move {
+            match download_fits(session_id).await {
+                Ok(url) => {
+                    message.set(None);
+                    let _ = window().location().set_href(&url);
+                }
+                Err(e) => message.set(Some(format!("Error: {}", e))),
+            }
+        });
+    };
+    view! {
+        <div class="mt-4">
+            <button
+                on:click=download
+                class="px-3 py-1 bg-blue-600 text-white text-sm rounded hover:bg-blue-700 transition-colors"
+            >
+                "Download FITS cube"
+            </button>
+            {move || message.get().map(|message| view! {
+                <p class="text-red-600 text-sm mt-2">{message}</p>
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn PriorSessions(generation_status: RwSignal<GenerationStatus>) -> impl IntoView {
+    let sessions = RwSignal::new(Vec::<PriorSession>::new());
+    let message = RwSignal::new(None::<String>);
+    let load = move |_| {
+        spawn_local(async move {
+            match list_sessions().await {
+                Ok(list) => {
+                    message.set(
+                        list.is_empty()
+                            .then(|| "No previous session found".to_string()),
+                    );
+                    sessions.set(list);
+                }
+                Err(e) => message.set(Some(format!("Error: {}", e))),
+            }
+        });
+    };
+    view! {
+        <div class="bg-white rounded-lg shadow-md p-6">
+            <h3 class="text-lg font-semibold mb-4 text-gray-800">"Previous Sessions"</h3>
+            <button
+                on:click=load
+                class="px-3 py-1 bg-blue-600 text-white text-sm rounded hover:bg-blue-700 transition-colors"
+            >
+                "Load previous sessions"
+            </button>
+            {move || message.get().map(|message| view! {
+                <p class="text-gray-500 text-sm mt-2">{message}</p>
+            })}
+            <ul class="mt-4 space-y-2">
+                {move || sessions.get().into_iter().map(|session| {
+                    let label = format!(
+                        "{} {}",
+                        session.cfd_case.clone().unwrap_or_else(|| "Unknown CFD case".to_string()),
+                        session.turbulence_effects.clone().map_or(String::new(), |effects| format!("({})", effects)),
+                    );
+                    let revisit = move |_| {
+                        generation_status.set(GenerationStatus {
+                            session_id: session.session_id.clone(),
+                            status: ProcessingStatus::Complete,
+                            message: format!("Session {}", session.session_id),
+                            progress: None,
+                            images: session.images.clone(),
+                        });
+                    };
+                    view! {
+                        <li>
+                            <button on:click=revisit class="text-blue-600 text-sm hover:underline">
+                                {label}
+                            </button>
+                        </li>
+                    }
+                }).collect::<Vec<_>>()}
+            </ul>
+        </div>
+    }
+}
@@ -3,8 +3,6 @@ pub mod components;
 pub mod server;
 // pub mod claude;
 
-const N_SAMPLE: usize = 100;
-
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
 pub fn hydrate() {
@@ -2,24 +2,92 @@ use std::{env, path::PathBuf};
 
 use leptos::prelude::*;
 
-use crate::components::{form_controls::PsfConfig, psf_generator::GeneratedImage};
+use psf::RunConfig;
+
+use crate::components::psf_generator::{GeneratedImage, PriorSession};
+
+/// Maximum number of sessions whose PSF frames are kept in memory, see [PENDING_CUBES]
+#[cfg(feature = "ssr")]
+const PENDING_CUBES_CAPACITY: usize = 4;
+
+/// PSF frames of the latest sessions, kept until the FITS cube of the session is first requested
+///
+/// Beyond [PENDING_CUBES_CAPACITY] sessions, the FITS cube of the oldest session
+/// is written to the session directory and its frames are dropped
+#[cfg(feature = "ssr")]
+static PENDING_CUBES: std::sync::LazyLock<
+    std::sync::Mutex<std::collections::VecDeque<(String, std::sync::Arc<psf::PSFs>)>>,
+> = std::sync::LazyLock::new(|| std::sync::Mutex::new(std::collections::VecDeque::new()));
 
 #[cfg(feature = "ssr")]
 static FRAME_ID: std::sync::LazyLock<std::sync::atomic::AtomicUsize> =
     std::sync::LazyLock::new(|| std::sync::atomic::AtomicUsize::new(0));
 
+/// Total number of frames of the current PSF generation
+#[cfg(feature = "ssr")]
+static FRAME_COUNT: std::sync::LazyLock<std::sync::atomic::AtomicUsize> =
+    std::sync::LazyLock::new(|| std::sync::atomic::AtomicUsize::new(n_sample()));
+
+/// PNG preview of the latest frame of the sessions being generated
+#[cfg(feature = "ssr")]
+static PREVIEWS: std::sync::LazyLock<std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+
+/// Removes the preview of a session when dropped, whether the generation completes or fails
+#[cfg(feature = "ssr")]
+struct PreviewGuard<'a>(&'a str);
+
+#[cfg(feature = "ssr")]
+impl Drop for PreviewGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut previews) = PREVIEWS.lock() {
+            previews.remove(self.0);
+        }
+    }
+}
+
+/// Binning factor of the preview frames
+#[cfg(feature = "ssr")]
+const PREVIEW_BIN: usize = 4;
+
+/// Frame rate of the animated GIFs
+#[cfg(feature = "ssr")]
+const ANIMATION_FPS: f64 = 5.;
+
+/// Default number of frames of a PSF generation
+#[cfg(feature = "ssr")]
+const DEFAULT_N_SAMPLE: usize = 100;
+
+/// Returns the number of frames of a PSF generation
+///
+/// The number of frames is read from the `PSF_N_SAMPLE` environment variable
+/// and defaults to 100
+#[cfg(feature = "ssr")]
+fn n_sample() -> usize {
+    env::var("PSF_N_SAMPLE")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_N_SAMPLE)
+}
+
+/// Returns the number of frames of a PSF generation configured on the server
+#[server]
+pub async fn get_n_sample() -> Result<usize, ServerFnError> {
+    Ok(n_sample())
+}
+
 #[server]
 pub async fn psf_generation(
-    config: PsfConfig,
+    run_config: RunConfig,
     session_id: String,
 ) -> Result<Vec<GeneratedImage>, ServerFnError> {
-    use crate::N_SAMPLE;
     use object_store::{path::Path, ObjectStore};
     use parse_monitors::{
         cfd::{Baseline, BaselineTrait, CfdCase},
         CFD_YEAR,
     };
-    use psf::{get_enclosure_config, GmtOpticalModel, PSFs, StorePath, ZenithAngle};
+    use psf::{GmtOpticalModel, GmtOpticalModelError, PSFs, StorePath};
     use std::{
         env,
         fs::create_dir_all,
@@ -44,7 +112,7 @@ pub async fn psf_generation(
     create_dir_all(&output_dir)?;
 
     let psf_path = format!("{}/psf.png", output_dir);
-    gmt.ray_trace().read_detector().save(&psf_path)?;
+    gmt.reference_psf()?.save(&psf_path)?;
 
     images.push(GeneratedImage {
         name: "Diffraction Limited".to_string(),
@@ -53,41 +121,36 @@ pub async fn psf_generation(
     });
 
     // Generate turbulence effects string
-    let turbulence_effects = match (config.domeseeing, config.windloads) {
-        (true, true) => Some(format!("Dome Seeing + {}", config.rbm_time_series)),
-        (true, false) => Some("Dome Seeing".to_string()),
-        (false, true) => Some(config.rbm_time_series.to_string()),
-        (false, false) => return Ok(vec![]),
+    let Some(turbulence_effects) = run_config.turbulence_effects() else {
+        return Ok(vec![]);
     };
-
-    if let Some(effects) = turbulence_effects {
-        gmt.set_config(gmt.get_config().turbulence_effects(effects));
-    }
+    gmt.set_config(gmt.get_config().turbulence_effects(turbulence_effects));
 
     // CFD case configuration
-    let zenith = ZenithAngle::from(config.elevation_angle).as_u32();
-    let azimuth = config.azimuth_angle.as_u32();
-    let wind_speed = config.wind_speed.as_u32();
-    let enclosure = get_enclosure_config(wind_speed, config.elevation_angle);
+    let zenith = run_config.zenith_angle.as_u32();
+    let azimuth = run_config.azimuth_angle.as_u32();
+    let wind_speed = run_config.wind_speed.as_u32();
+    let enclosure = run_config.enclosure();
 
-    let cfd_case = CfdCase::<CFD_YEAR>::colloquial(zenith, azimuth, enclosure, wind_speed)?;
+    let cfd_case = CfdCase::<CFD_YEAR>::colloquial(zenith, azimuth, enclosure, wind_speed)
+        .map_err(|_| GmtOpticalModelError::invalid_cfd_case(&run_config, CFD_YEAR))?;
     gmt.set_config(gmt.get_config().cfd_case(cfd_case.to_string()));
 
     // Setup dome seeing if requested
-    let gmt = if config.domeseeing {
+    let gmt = if run_config.domeseeing {
         let cfd_path =
             Path::from(Baseline::<CFD_YEAR>::path()?.to_str().unwrap()).join(cfd_case.to_string());
-        gmt.domeseeing(store.clone(), cfd_path).await?
+        gmt.domeseeing_from_store(store.clone(), cfd_path).await?
     } else {
         gmt
     };
 
     // Setup wind loads if requested
-    let mut gmt = if config.windloads {
+    let mut gmt = if let Some(series) = run_config.windloads {
         let rbms_path = Path::new(env::var("FEM")?)
             .join("cfd")
             .join(cfd_case)
-            .join(config.rbm_time_series.file_name());
+            .join(series.file_name());
         leptos::logging::log!("{}", rbms_path);
         gmt.windloads(store.clone(), rbms_path).await?
     } else {
@@ -97,24 +160,42 @@ pub async fn psf_generation(
     // Process turbulence-affected frames
     let mut psfs = PSFs::from(&gmt);
 
-    for i in 0..N_SAMPLE {
+    let n_frame = n_sample();
+    FRAME_COUNT.store(n_frame, Ordering::Relaxed);
+    let preview = PreviewGuard(&session_id);
+    for i in 0..n_frame {
         FRAME_ID.store(i, Ordering::Relaxed);
-        psfs.push(
-            gmt.async_ray_trace()
-                .await
-                .read_detector()
-                .opd(gmt.get_opd())
-                .pssn_value(gmt.compute_pssn()),
-        );
+        let psf = gmt
+            .async_ray_trace()
+            .await
+            .read_detector()?
+            .opd(gmt.get_opd())
+            .pssn_value(gmt.compute_pssn());
+        match psf.to_preview_png_bytes(PREVIEW_BIN) {
+            Ok(png) => {
+                PREVIEWS.lock().unwrap().insert(session_id.clone(), png);
+            }
+            Err(e) => leptos::logging::warn!("failed to encode the preview of frame #{}: {}", i, e),
+        }
+        psfs.push(psf);
     }
+    drop(preview);
 
     // Setup output directory for frames
     let frames_dir = format!("{}/frames", output_dir);
     // Save all turbulence frames with consistent normalization
-    psfs.save_all_frames(frames_dir, &*FRAME_ID)?;
+    psfs.save_all_frames(frames_dir, &*FRAME_ID, None)?;
 
     let long_exposure_path = format!("{}/long_exposure_psf.png", output_dir);
     psfs.sum().save(&long_exposure_path)?;
+    psfs.save_metadata(&output_dir)?;
+    gmt.get_config()
+        .to_json(format!("{}/config.json", output_dir))?;
+    // Run configuration, read back by the command line interface with `--from-config`
+    serde_json::to_writer_pretty(
+        std::fs::File::create(format!("{}/run_config.json", output_dir))?,
+        &run_config,
+    )?;
     images.push(GeneratedImage {
         name: "Long exposure PSF".to_string(),
         path: format!("generated/{}/long_exposure_psf.png", session_id),
@@ -131,21 +212,147 @@ pub async fn psf_generation(
         psfs.len(),
         output_dir
     );
+    let evicted = {
+        let mut pending = PENDING_CUBES.lock().unwrap();
+        pending.push_back((session_id, Arc::new(psfs)));
+        if pending.len() > PENDING_CUBES_CAPACITY {
+            pending.pop_front()
+        } else {
+            None
+        }
+    };
+    if let Some((id, psfs)) = evicted {
+        if let Err(e) = psfs.save_fits_cube(fits_cube_path(&id)) {
+            leptos::logging::warn!("failed to write the FITS cube of session {}: {}", id, e);
+        }
+    }
     Ok(dbg!(images))
 }
+/// Returns the URL of the FITS cube of a session
+#[cfg(feature = "ssr")]
+fn fits_cube_url(session_id: &str) -> String {
+    format!("generated/{}/psf_cube.fits", session_id)
+}
+/// Returns the path of the FITS cube of a session
+#[cfg(feature = "ssr")]
+fn fits_cube_path(session_id: &str) -> PathBuf {
+    std::path::Path::new("target")
+        .join("site")
+        .join(fits_cube_url(session_id))
+}
+/// Returns the PSF frames of a session if they are still pending, see [PENDING_CUBES]
+///
+/// The lock is released before returning so the frames can be encoded without blocking
+/// the other sessions
+#[cfg(feature = "ssr")]
+fn pending_cube(session_id: &str) -> Option<std::sync::Arc<psf::PSFs>> {
+    PENDING_CUBES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(id, _)| id == session_id)
+        .map(|(_, psfs)| psfs.clone())
+}
+/// Returns the URL of the FITS cube of the PSF frames of a session
+///
+/// The cube is written on the first request, or when the session is evicted
+/// from [PENDING_CUBES], and then served from the disk
+#[server]
+pub async fn download_fits(session_id: String) -> Result<String, ServerFnError> {
+    let url = fits_cube_url(&session_id);
+    let path = fits_cube_path(&session_id);
+    if path.exists() {
+        return Ok(url);
+    }
+    let Some(psfs) = pending_cube(&session_id) else {
+        return Err(ServerFnError::new(format!(
+            "the PSF frames of session {} are not available anymore",
+            session_id
+        )));
+    };
+    psfs.save_fits_cube(&path)?;
+    PENDING_CUBES
+        .lock()
+        .unwrap()
+        .retain(|(id, _)| *id != session_id);
+    Ok(url)
+}
+/// Creates the animated GIF `gif` of the PSF frames or, if `prefix` is `opd`, of the OPD maps
+///
+/// The frames of the session are encoded from memory while they are pending,
+/// see [PENDING_CUBE], and otherwise from the `<prefix>_*.png` images in `frames_dir`
+#[cfg(feature = "ssr")]
+fn animate(
+    session_id: Option<&str>,
+    frames_dir: &std::path::Path,
+    prefix: &str,
+    gif: &std::path::Path,
+) -> Result<(), ServerFnError> {
+    if let Some(psfs) = session_id.and_then(pending_cube) {
+        match prefix {
+            "opd" => psfs.save_opd_animation(gif, ANIMATION_FPS)?,
+            _ => psfs.save_animation(gif, ANIMATION_FPS)?,
+        }
+        return Ok(());
+    }
+    encode_gif(frames_dir, prefix, gif)
+}
+
+/// Encodes the `<prefix>_*.png` images in `frames_dir` into the animated GIF `gif`
+#[cfg(feature = "ssr")]
+fn encode_gif(
+    frames_dir: &std::path::Path,
+    prefix: &str,
+    gif: &std::path::Path,
+) -> Result<(), ServerFnError> {
+    use image::{
+        codecs::gif::{GifEncoder, Repeat},
+        Delay, Frame,
+    };
+    use std::fs::{self, File};
+
+    let mut pngs: Vec<_> = fs::read_dir(frames_dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "png")
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&format!("{}_", prefix)))
+        })
+        .collect();
+    if pngs.is_empty() {
+        return Err(ServerFnError::new(format!(
+            "no {}_*.png images found in {:?}",
+            prefix, frames_dir
+        )));
+    }
+    pngs.sort();
+    let mut encoder = GifEncoder::new(File::create(gif)?);
+    encoder.set_repeat(Repeat::Infinite)?;
+    for png in pngs {
+        let frame = Frame::from_parts(
+            image::open(&png)?.to_rgba8(),
+            0,
+            0,
+            Delay::from_numer_denom_ms(1000, ANIMATION_FPS as u32),
+        );
+        encoder.encode_frame(frame)?;
+    }
+    Ok(())
+}
+
 #[server]
 pub async fn psf_animation(output_dir: PathBuf) -> Result<GeneratedImage, ServerFnError> {
-    use std::{path::Path, process::Command};
-    println!("   convert -delay 20 -loop 0 frames/frame_*.png psf_animation.gif");
+    use std::path::Path;
     let root = Path::new("target").join("site").join(&output_dir);
-    Command::new("/usr/bin/convert")
-        .arg("-delay")
-        .arg("20")
-        .arg("-loop")
-        .arg("0")
-        .arg(root.join("frames").join("frame_*.png"))
-        .arg(root.join("psf_animation.gif"))
-        .output()?;
+    animate(
+        output_dir.file_name().and_then(|name| name.to_str()),
+        &root.join("frames"),
+        "frame",
+        &root.join("psf_animation.gif"),
+    )?;
     Ok(GeneratedImage {
         name: "Short exposure PSFs animation".to_string(),
         path: format!(
@@ -158,17 +365,14 @@ pub async fn psf_animation(output_dir: PathBuf) -> Result<GeneratedImage, Server
 }
 #[server]
 pub async fn opd_animation(output_dir: PathBuf) -> Result<GeneratedImage, ServerFnError> {
-    use std::{path::Path, process::Command};
-    println!("   convert -delay 20 -loop 0 frames/opd_*.png opd_animation.gif");
+    use std::path::Path;
     let root = Path::new("target").join("site").join(&output_dir);
-    Command::new("/usr/bin/convert")
-        .arg("-delay")
-        .arg("20")
-        .arg("-loop")
-        .arg("0")
-        .arg(root.join("frames").join("opd_*.png"))
-        .arg(root.join("opd_animation.gif"))
-        .output()?;
+    animate(
+        output_dir.file_name().and_then(|name| name.to_str()),
+        &root.join("frames"),
+        "opd",
+        &root.join("opd_animation.gif"),
+    )?;
     Ok(GeneratedImage {
         name: "Short exposure OPDs animation".to_string(),
         path: format!(
@@ -180,7 +384,106 @@ pub async fn opd_animation(output_dir: PathBuf) -> Result<GeneratedImage, Server
     })
 }
 
+/// Returns the latest frame of a session being generated as a PNG data URL
+///
+/// Returns `None` if the session is not being generated
+#[server]
+pub async fn get_preview(session_id: String) -> Result<Option<String>, ServerFnError> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    Ok(PREVIEWS
+        .lock()
+        .unwrap()
+        .get(&session_id)
+        .map(|png| format!("data:image/png;base64,{}", STANDARD.encode(png))))
+}
+
+/// Returns the index of the frame being processed and the total number of frames
+#[server]
+pub async fn get_frame_id() -> Result<(usize, usize), ServerFnError> {
+    use std::sync::atomic::Ordering;
+    Ok((
+        FRAME_ID.load(Ordering::Relaxed),
+        FRAME_COUNT.load(Ordering::Relaxed),
+    ))
+}
+
+/// Lists the previous sessions found in `target/site/generated/`, the most recent first
+///
+/// Each session is labeled with the CFD case and turbulence effects of its
+/// `run_metadata.json` sidecar, if any
 #[server]
-pub async fn get_frame_id() -> Result<usize, ServerFnError> {
-    Ok(FRAME_ID.load(std::sync::atomic::Ordering::Relaxed))
+pub async fn list_sessions() -> Result<Vec<PriorSession>, ServerFnError> {
+    use std::{fs, path::Path, time::SystemTime};
+
+    const IMAGES: [(&str, &str, &str); 4] = [
+        (
+            "psf.png",
+            "Diffraction Limited",
+            "GMT diffraction limited PSF",
+        ),
+        (
+            "long_exposure_psf.png",
+            "Long exposure PSF",
+            "GMT long exposure CFD PSF",
+        ),
+        (
+            "psf_animation.gif",
+            "Short exposure PSFs animation",
+            "GMT short exposure CFD PSFs animation",
+        ),
+        (
+            "opd_animation.gif",
+            "Short exposure OPDs animation",
+            "GMT CFD OPDs animation",
+        ),
+    ];
+
+    let root = Path::new("target").join("site").join("generated");
+    let Ok(entries) = fs::read_dir(&root) else {
+        return Ok(vec![]);
+    };
+    let mut sessions = vec![];
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let session_id = entry.file_name().to_string_lossy().to_string();
+        let metadata: Option<serde_json::Value> = fs::read(dir.join("run_metadata.json"))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok());
+        let field = |key: &str| {
+            metadata
+                .as_ref()
+                .and_then(|metadata| metadata[key].as_str())
+                .map(|value| value.to_string())
+        };
+        let images: Vec<_> = IMAGES
+            .iter()
+            .filter(|(file, _, _)| dir.join(file).exists())
+            .map(|(file, name, description)| GeneratedImage {
+                name: name.to_string(),
+                path: format!("generated/{}/{}", session_id, file),
+                description: description.to_string(),
+            })
+            .collect();
+        if images.is_empty() {
+            continue;
+        }
+        let modified = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        sessions.push((
+            modified,
+            PriorSession {
+                cfd_case: field("cfd_case"),
+                turbulence_effects: field("turbulence_effects"),
+                session_id,
+                images,
+            },
+        ));
+    }
+    sessions.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(sessions.into_iter().map(|(_, session)| session).collect())
 }